@@ -1,13 +1,16 @@
 use bench_matrix::{
   criterion_runner::async_suite::AsyncBenchmarkSuite,
-  AbstractCombination, MatrixCellValue,
+  AbstractCombination, MatrixCellValue, MetricsRecorder, OpenLoop,
 };
 use criterion::{criterion_group, criterion_main, AxisScale, BenchmarkGroup, Criterion, PlotConfiguration, Throughput};
 use rand::prelude::*;
 use std::{
   future::Future,
   pin::Pin,
-  sync::atomic::{AtomicUsize, Ordering},
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
   time::{Duration, Instant},
 };
 use tokio::runtime::Runtime;
@@ -36,6 +39,8 @@ struct AsyncState {
   simulated_connections: Vec<String>,
 }
 
+type AsyncSetupFuture = Pin<Box<dyn Future<Output = Result<(AsyncContext, AsyncState), String>> + Send>>;
+
 static ASYNC_GLOBAL_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 // Extractor function remains the same as it operates on AbstractCombination indices
@@ -68,7 +73,7 @@ fn async_global_setup(cfg: &ConfigAsync) -> Result<(), String> {
 fn async_setup_fn(
   _runtime: &Runtime,
   cfg: &ConfigAsync,
-) -> Pin<Box<dyn Future<Output = Result<(AsyncContext, AsyncState), String>> + Send>> {
+) -> AsyncSetupFuture {
   let cfg_clone = cfg.clone();
   Box::pin(async move {
     tokio::time::sleep(Duration::from_micros(10)).await;
@@ -188,10 +193,92 @@ pub fn benchmark_async_suite_named(c: &mut Criterion) {
     } else {
         Throughput::Elements(1) // Default for single operation if concurrent_ops is 0
     }
+  })
+  // Aggregated per variant in bench_matrix's own report, printed after the suite.
+  .collect_metrics(|ctx: &mut AsyncContext, state: &AsyncState, _cfg: &ConfigAsync, metrics: &mut MetricsRecorder| {
+    metrics.record("ops", std::mem::take(&mut ctx.ops_this_iteration) as f64);
+    metrics.record("packet_bytes", state.data_packet.len() as f64);
+    metrics.record("open_connections", state.simulated_connections.len() as f64);
   });
 
   async_suite.run();
 }
 
-criterion_group!(async_benches_named, benchmark_async_suite_named);
+// --- Open-loop load generation ---
+
+#[derive(Debug, Clone)]
+pub struct ConfigOpenLoop {
+  pub rate: u64,
+  pub service_time: Duration,
+}
+
+fn extract_open_loop_config(combo: &AbstractCombination) -> Result<ConfigOpenLoop, String> {
+  Ok(ConfigOpenLoop {
+    rate: combo.get_u64(0)?,
+    service_time: Duration::from_micros(combo.get_u64(1)?),
+  })
+}
+
+fn open_loop_setup_fn(_runtime: &Runtime, _cfg: &ConfigOpenLoop) -> AsyncSetupFuture {
+  Box::pin(async move {
+    Ok((
+      AsyncContext::default(),
+      AsyncState {
+        data_packet: vec![0; 64],
+        simulated_connections: Vec::new(),
+      },
+    ))
+  })
+}
+
+// One simulated request; the state is shared by every request in flight.
+fn open_loop_request_fn(state: Arc<AsyncState>, cfg: &ConfigOpenLoop) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+  let service_time = cfg.service_time;
+  Box::pin(async move {
+    tokio::time::sleep(service_time).await;
+    let _checksum = state.data_packet.iter().fold(0u8, |acc, &x| acc.wrapping_add(x));
+  })
+}
+
+fn open_loop_unused_logic_fn(
+  ctx: AsyncContext,
+  state: AsyncState,
+  _cfg: &ConfigOpenLoop,
+) -> Pin<Box<dyn Future<Output = (AsyncContext, AsyncState, Duration)> + Send>> {
+  Box::pin(async move { (ctx, state, Duration::ZERO) })
+}
+
+fn open_loop_teardown_fn(
+  _ctx: AsyncContext,
+  _state: AsyncState,
+  _runtime: &Runtime,
+  _cfg: &ConfigOpenLoop,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+  Box::pin(async {})
+}
+
+pub fn benchmark_async_open_loop(c: &mut Criterion) {
+  let rt = Runtime::new().expect("Failed to create Tokio runtime for open-loop benchmarks");
+
+  AsyncBenchmarkSuite::new(
+    c,
+    &rt,
+    "AsyncOpenLoop".to_string(),
+    Some(vec!["Rate".to_string(), "ServiceMicros".to_string()]),
+    vec![
+      vec![MatrixCellValue::Unsigned(1_000), MatrixCellValue::Unsigned(10_000)],
+      vec![MatrixCellValue::Unsigned(500)],
+    ],
+    Box::new(extract_open_loop_config),
+    open_loop_setup_fn,
+    open_loop_unused_logic_fn,
+    open_loop_teardown_fn,
+  )
+  .open_loop(
+    OpenLoop::new(open_loop_request_fn, |cfg: &ConfigOpenLoop| cfg.rate as f64).duration(Duration::from_secs(1)),
+  )
+  .run_open_loop();
+}
+
+criterion_group!(async_benches_named, benchmark_async_suite_named, benchmark_async_open_loop);
 criterion_main!(async_benches_named);
\ No newline at end of file
@@ -0,0 +1,106 @@
+use bench_matrix::{bench_matrix, AbstractCombination};
+use criterion::Throughput;
+use std::{
+  future::Future,
+  pin::Pin,
+  time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+// --- Configuration shared by both suites ---
+#[derive(Debug, Clone)]
+pub struct ConfigMacro {
+  pub elements: usize,
+  pub reverse: bool,
+}
+
+fn extract_macro_config(combo: &AbstractCombination) -> Result<ConfigMacro, String> {
+  Ok(ConfigMacro {
+    elements: combo.get_u64(0)? as usize,
+    reverse: combo.get_bool(1)?,
+  })
+}
+
+fn build_dataset(cfg: &ConfigMacro) -> Vec<u64> {
+  let mut data: Vec<u64> = (0..cfg.elements as u64).collect();
+  if cfg.reverse {
+    data.reverse();
+  }
+  data
+}
+
+// --- Sync lifecycle ---
+fn sync_setup_fn(cfg: &ConfigMacro) -> Result<((), Vec<u64>), String> {
+  Ok(((), build_dataset(cfg)))
+}
+
+fn sync_logic_fn(ctx: (), data: Vec<u64>, _cfg: &ConfigMacro) -> ((), Vec<u64>, Duration) {
+  let start_time = Instant::now();
+  let mut sorted = data.clone();
+  sorted.sort_unstable();
+  let duration = start_time.elapsed();
+  (ctx, data, duration)
+}
+
+fn sync_teardown_fn(_ctx: (), _data: Vec<u64>, _cfg: &ConfigMacro) {}
+
+// --- Async lifecycle ---
+type AsyncSetupFuture = Pin<Box<dyn Future<Output = Result<((), Vec<u64>), String>> + Send>>;
+type AsyncLogicFuture = Pin<Box<dyn Future<Output = ((), Vec<u64>, Duration)> + Send>>;
+
+fn async_setup_fn(_runtime: &Runtime, cfg: &ConfigMacro) -> AsyncSetupFuture {
+  let data = build_dataset(cfg);
+  Box::pin(async move { Ok(((), data)) })
+}
+
+fn async_logic_fn(
+  ctx: (),
+  data: Vec<u64>,
+  _cfg: &ConfigMacro,
+) -> AsyncLogicFuture {
+  Box::pin(async move {
+    let start_time = Instant::now();
+    tokio::task::yield_now().await;
+    let _sum: u64 = data.iter().sum();
+    let duration = start_time.elapsed();
+    (ctx, data, duration)
+  })
+}
+
+fn async_teardown_fn(
+  _ctx: (),
+  _data: Vec<u64>,
+  _runtime: &Runtime,
+  _cfg: &ConfigMacro,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+  Box::pin(async {})
+}
+
+bench_matrix! {
+  name: macro_suites;
+  sync "MacroSyncSuite" {
+    config: ConfigMacro,
+    axes: {
+      "Elements" => [100u64, 1_000u64],
+      "Reverse" => [false, true],
+    },
+    extractor: extract_macro_config,
+    setup: sync_setup_fn,
+    logic: sync_logic_fn,
+    teardown: sync_teardown_fn,
+    with: {
+      throughput(|cfg: &ConfigMacro| Throughput::Elements(cfg.elements as u64)),
+    },
+  }
+  async(Runtime::new().expect("Failed to create Tokio runtime for macro benchmarks")) "MacroAsyncSuite" {
+    config: ConfigMacro,
+    axes: {
+      "Elements" => [100u64],
+      "Reverse" => [false, true],
+    },
+    extractor: extract_macro_config,
+    setup: async_setup_fn,
+    logic: async_logic_fn,
+    teardown: async_teardown_fn,
+  }
+}
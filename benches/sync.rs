@@ -172,7 +172,14 @@ pub fn benchmark_sync_suite_named(c: &mut Criterion) {
       .sample_size(15) // Adjusted from original example
       .measurement_time(Duration::from_secs(2)); // Adjusted
   })
-  .throughput(|cfg: &ConfigSync| Throughput::Elements(cfg.data_elements as u64));
+  .throughput(|cfg: &ConfigSync| Throughput::Elements(cfg.data_elements as u64))
+  // Reported only in bench_matrix's own summary, next to the primary throughput.
+  .secondary_throughput("dataset", |cfg: &ConfigSync| {
+    Throughput::Bytes((cfg.data_elements * std::mem::size_of::<u64>()) as u64)
+  })
+  .throughput_from_state("processed", |ctx: &SyncContext, _state: &SyncState, _cfg: &ConfigSync| {
+    Throughput::Elements(ctx.items_processed_in_batch as u64)
+  });
 
   sync_suite.run();
 }
@@ -0,0 +1,489 @@
+#![cfg(feature = "json")]
+
+//! Comparing a suite's results against a saved baseline, with a verdict for the whole matrix.
+//!
+//! A [`ResultSet`] holds Criterion's mean estimate of each variant of a suite, loaded from a
+//! baseline Criterion saved (`--save-baseline <name>`, or `new` for the latest run) or from a
+//! file written by [`ResultsTable::write`](crate::export::ResultsTable::write).
+//! [`ComparisonReport`] pairs the variants of two sets by benchmark ID and computes the relative
+//! change of each mean. A change is significant when the confidence intervals of the two means
+//! do not overlap; a significant slowdown beyond the threshold is a regression, and
+//! [`ComparisonReport::check`] fails if there is any, so CI can gate merges on it.
+//!
+//! ```no_run
+//! use bench_matrix::compare::{ComparisonReport, ResultSet};
+//!
+//! let baseline = ResultSet::from_baseline("Sort", "main").expect("failed to load the baseline");
+//! let current = ResultSet::from_baseline("Sort", "new").expect("failed to load the latest run");
+//! let comparison = ComparisonReport::new(&baseline, &current, 0.05);
+//! println!("{}", comparison.render());
+//! if let Err(e) = comparison.check() {
+//!   eprintln!("{}", e);
+//!   std::process::exit(1);
+//! }
+//! ```
+
+use crate::export::{criterion_output_dir, index_benchmarks, read_estimates, Estimate, ExportError, ResultsTable};
+use crate::id::format_duration;
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::path::Path;
+use std::time::Duration;
+
+/// The mean time of each variant of a suite, from one run.
+#[derive(Debug, Clone, Default)]
+pub struct ResultSet {
+  /// Where the results came from: the baseline name or the file path.
+  pub name: String,
+  /// Each variant's full benchmark ID with Criterion's estimate of its mean, in nanoseconds.
+  pub means: Vec<(String, Estimate)>,
+}
+
+impl ResultSet {
+  /// Loads the suite's results saved by Criterion under `baseline` (`new` for the latest run)
+  /// from its default output directory.
+  pub fn from_baseline(suite_name: &str, baseline: &str) -> Result<Self, ExportError> {
+    Self::from_baseline_dir(suite_name, baseline, criterion_output_dir())
+  }
+
+  /// Loads the suite's results saved by Criterion under `baseline` from `criterion_dir`.
+  /// Benchmarks of the suite's split groups (`Suite/Part`) are included.
+  pub fn from_baseline_dir(suite_name: &str, baseline: &str, criterion_dir: impl AsRef<Path>) -> Result<Self, ExportError> {
+    let criterion_dir = criterion_dir.as_ref();
+    let mut benchmark_dirs = HashMap::new();
+    if criterion_dir.is_dir() {
+      index_benchmarks(criterion_dir, baseline, &mut benchmark_dirs)?;
+    }
+    let prefix = format!("{}/", suite_name);
+    let mut means = benchmark_dirs
+      .into_iter()
+      .filter(|(id, _)| id.starts_with(&prefix))
+      .map(|(id, dir)| Ok((id, read_estimates(&dir.join("estimates.json"))?.mean)))
+      .collect::<Result<Vec<_>, ExportError>>()?;
+    if means.is_empty() {
+      return Err(ExportError::NoResults {
+        path: criterion_dir.to_path_buf(),
+        message: format!("no results for suite '{}' in baseline '{}'", suite_name, baseline),
+      });
+    }
+    means.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(Self {
+      name: baseline.to_string(),
+      means,
+    })
+  }
+
+  /// Loads results exported as CSV or JSON, chosen by the file extension. Rows without a mean
+  /// are skipped.
+  pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ExportError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|source| ExportError::Io {
+      path: path.to_path_buf(),
+      source,
+    })?;
+    let parse_error = |message: String| ExportError::Parse {
+      path: path.to_path_buf(),
+      message,
+    };
+    let rows: Vec<HashMap<String, Value>> = match path.extension().and_then(|extension| extension.to_str()) {
+      Some(extension) if extension.eq_ignore_ascii_case("json") => serde_json::from_str(&text).map_err(|e| parse_error(e.to_string()))?,
+      Some(extension) if extension.eq_ignore_ascii_case("csv") => parse_csv(&text),
+      _ => return Err(ExportError::UnsupportedFormat { path: path.to_path_buf() }),
+    };
+
+    let mut means = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+      let number = |column: &str| match row.get(column) {
+        Some(Value::Number(number)) => number.as_f64(),
+        Some(Value::String(text)) => text.parse().ok(),
+        _ => None,
+      };
+      let id = match row.get("id") {
+        Some(Value::String(id)) => id.clone(),
+        _ => return Err(parse_error(format!("row {} has no 'id'", index + 1))),
+      };
+      if let Some(point) = number("mean_ns") {
+        let estimate = Estimate {
+          point,
+          lower: number("mean_lower_ns").unwrap_or(point),
+          upper: number("mean_upper_ns").unwrap_or(point),
+        };
+        means.push((id, estimate));
+      }
+    }
+    if means.is_empty() {
+      return Err(ExportError::NoResults {
+        path: path.to_path_buf(),
+        message: "no row has a 'mean_ns'".to_string(),
+      });
+    }
+    Ok(Self {
+      name: path.display().to_string(),
+      means,
+    })
+  }
+
+  /// Returns the mean of the variant with the given full benchmark ID.
+  pub fn mean(&self, id: &str) -> Option<Estimate> {
+    self.means.iter().find(|(mean_id, _)| mean_id == id).map(|(_, mean)| *mean)
+  }
+}
+
+/// The variants of a table that have Criterion estimates.
+impl From<&ResultsTable> for ResultSet {
+  fn from(table: &ResultsTable) -> Self {
+    Self {
+      name: "current run".to_string(),
+      means: table
+        .rows
+        .iter()
+        .filter_map(|row| Some((row.id.clone(), row.estimates?.mean)))
+        .collect(),
+    }
+  }
+}
+
+/// How a variant changed between the baseline and the current results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+  /// Significantly slower, by more than the threshold.
+  Regressed,
+  /// Significantly faster, by more than the threshold.
+  Improved,
+  Unchanged,
+  /// Only in the current results.
+  Added,
+  /// Only in the baseline.
+  Removed,
+}
+
+impl fmt::Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Verdict::Regressed => write!(f, "regressed"),
+      Verdict::Improved => write!(f, "improved"),
+      Verdict::Unchanged => write!(f, "unchanged"),
+      Verdict::Added => write!(f, "added"),
+      Verdict::Removed => write!(f, "removed"),
+    }
+  }
+}
+
+/// One variant compared across the two result sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantComparison {
+  /// The full benchmark ID.
+  pub id: String,
+  pub baseline: Option<Estimate>,
+  pub current: Option<Estimate>,
+  /// The relative change of the mean, e.g. `0.1` for 10% slower. `None` unless the variant is
+  /// in both sets.
+  pub change: Option<f64>,
+  /// Whether the confidence intervals of the two means do not overlap.
+  pub significant: bool,
+  pub verdict: Verdict,
+}
+
+/// Every variant of a suite compared between a baseline and the current results.
+#[derive(Debug, Clone)]
+pub struct ComparisonReport {
+  pub baseline_name: String,
+  pub current_name: String,
+  /// The relative slowdown beyond which a significant change is a regression, e.g. `0.05`.
+  pub threshold: f64,
+  /// The variants of the current results in their order, followed by the removed variants.
+  pub variants: Vec<VariantComparison>,
+}
+
+impl ComparisonReport {
+  /// Compares `current` with `baseline`. A significant change of the mean by more than
+  /// `threshold` (a fraction, e.g. `0.05` for 5%) either way is a regression or an improvement.
+  pub fn new(baseline: &ResultSet, current: &ResultSet, threshold: f64) -> Self {
+    let mut variants: Vec<VariantComparison> = current
+      .means
+      .iter()
+      .map(|(id, current_mean)| match baseline.mean(id) {
+        Some(baseline_mean) => {
+          let change = current_mean.point / baseline_mean.point - 1.0;
+          let significant = current_mean.lower > baseline_mean.upper || current_mean.upper < baseline_mean.lower;
+          let verdict = match change {
+            change if significant && change > threshold => Verdict::Regressed,
+            change if significant && change < -threshold => Verdict::Improved,
+            _ => Verdict::Unchanged,
+          };
+          VariantComparison {
+            id: id.clone(),
+            baseline: Some(baseline_mean),
+            current: Some(*current_mean),
+            change: Some(change),
+            significant,
+            verdict,
+          }
+        }
+        None => VariantComparison {
+          id: id.clone(),
+          baseline: None,
+          current: Some(*current_mean),
+          change: None,
+          significant: false,
+          verdict: Verdict::Added,
+        },
+      })
+      .collect();
+    for (id, baseline_mean) in &baseline.means {
+      if current.mean(id).is_none() {
+        variants.push(VariantComparison {
+          id: id.clone(),
+          baseline: Some(*baseline_mean),
+          current: None,
+          change: None,
+          significant: false,
+          verdict: Verdict::Removed,
+        });
+      }
+    }
+
+    Self {
+      baseline_name: baseline.name.clone(),
+      current_name: current.name.clone(),
+      threshold,
+      variants,
+    }
+  }
+
+  /// Returns the regressed variants.
+  pub fn regressions(&self) -> impl Iterator<Item = &VariantComparison> {
+    self.variants.iter().filter(|variant| variant.verdict == Verdict::Regressed)
+  }
+
+  /// Returns `Err` listing the regressed variants, if any.
+  pub fn check(&self) -> Result<(), RegressionError> {
+    let regressions: Vec<VariantComparison> = self.regressions().cloned().collect();
+    if regressions.is_empty() {
+      Ok(())
+    } else {
+      Err(RegressionError {
+        baseline_name: self.baseline_name.clone(),
+        threshold: self.threshold,
+        regressions,
+      })
+    }
+  }
+
+  /// Renders the comparison as an aligned table with one line per variant and a count of
+  /// each verdict.
+  pub fn render(&self) -> String {
+    let id_width = self.variants.iter().map(|variant| variant.id.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    let _ = writeln!(
+      out,
+      "Comparison of '{}' against baseline '{}' (threshold {:.1}%):",
+      self.current_name,
+      self.baseline_name,
+      self.threshold * 100.0
+    );
+    for variant in &self.variants {
+      let change = variant.change.map(format_change).unwrap_or_else(|| "-".to_string());
+      let _ = writeln!(
+        out,
+        "  {:<id_width$}  {:>10} -> {:<10}  {:>8}  {}",
+        variant.id,
+        format_mean(variant.baseline),
+        format_mean(variant.current),
+        change,
+        variant.verdict
+      );
+    }
+    let count = |verdict: Verdict| self.variants.iter().filter(|variant| variant.verdict == verdict).count();
+    let _ = writeln!(
+      out,
+      "{} regressed; {} improved; {} unchanged; {} added; {} removed",
+      count(Verdict::Regressed),
+      count(Verdict::Improved),
+      count(Verdict::Unchanged),
+      count(Verdict::Added),
+      count(Verdict::Removed)
+    );
+    out
+  }
+}
+
+/// The variants that regressed, returned by [`ComparisonReport::check`].
+#[derive(Debug, Clone)]
+pub struct RegressionError {
+  pub baseline_name: String,
+  pub threshold: f64,
+  pub regressions: Vec<VariantComparison>,
+}
+
+impl fmt::Display for RegressionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} variant(s) regressed by more than {:.1}% against baseline '{}'",
+      self.regressions.len(),
+      self.threshold * 100.0,
+      self.baseline_name
+    )?;
+    for variant in &self.regressions {
+      write!(
+        f,
+        "\n  {}: {} -> {} ({})",
+        variant.id,
+        format_mean(variant.baseline),
+        format_mean(variant.current),
+        variant.change.map(format_change).unwrap_or_default()
+      )?;
+    }
+    Ok(())
+  }
+}
+
+impl std::error::Error for RegressionError {}
+
+fn format_mean(mean: Option<Estimate>) -> String {
+  mean
+    .map(|mean| format_duration(Duration::from_secs_f64(mean.point.max(0.0) / 1e9)))
+    .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_change(change: f64) -> String {
+  format!("{:+.1}%", change * 100.0)
+}
+
+/// Parses CSV as written by `ResultsTable::to_csv` into one map per row, keyed by the header.
+fn parse_csv(text: &str) -> Vec<HashMap<String, Value>> {
+  let mut records: Vec<Vec<String>> = Vec::new();
+  let mut record = Vec::new();
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        chars.next();
+        field.push('"');
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => record.push(std::mem::take(&mut field)),
+      '\n' if !quoted => {
+        record.push(std::mem::take(&mut field));
+        records.push(std::mem::take(&mut record));
+      }
+      '\r' if !quoted => {}
+      c => field.push(c),
+    }
+  }
+  if !field.is_empty() || !record.is_empty() {
+    record.push(field);
+    records.push(record);
+  }
+
+  let mut records = records.into_iter();
+  let header = records.next().unwrap_or_default();
+  records
+    .map(|record| {
+      header
+        .iter()
+        .cloned()
+        .zip(record)
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(column, value)| (column, Value::String(value)))
+        .collect()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn estimate(point: f64, half_width: f64) -> Estimate {
+    Estimate {
+      point,
+      lower: point - half_width,
+      upper: point + half_width,
+    }
+  }
+
+  #[test]
+  fn test_only_significant_slowdowns_beyond_the_threshold_regress() {
+    let baseline = ResultSet {
+      name: "main".to_string(),
+      means: vec![
+        ("Sort/Size-1".to_string(), estimate(100.0, 2.0)),
+        ("Sort/Size-2".to_string(), estimate(100.0, 2.0)),
+        ("Sort/Size-3".to_string(), estimate(100.0, 20.0)),
+        ("Sort/Size-4".to_string(), estimate(100.0, 2.0)),
+        ("Sort/Size-5".to_string(), estimate(100.0, 2.0)),
+      ],
+    };
+    let current = ResultSet {
+      name: "new".to_string(),
+      means: vec![
+        ("Sort/Size-1".to_string(), estimate(120.0, 2.0)),
+        ("Sort/Size-2".to_string(), estimate(104.0, 1.0)),
+        ("Sort/Size-3".to_string(), estimate(130.0, 20.0)),
+        ("Sort/Size-4".to_string(), estimate(80.0, 2.0)),
+        ("Sort/Size-6".to_string(), estimate(50.0, 2.0)),
+      ],
+    };
+    let comparison = ComparisonReport::new(&baseline, &current, 0.05);
+    let verdicts: Vec<Verdict> = comparison.variants.iter().map(|variant| variant.verdict).collect();
+    assert_eq!(
+      verdicts,
+      [
+        Verdict::Regressed,
+        Verdict::Unchanged, // significant, but within the threshold
+        Verdict::Unchanged, // beyond the threshold, but the intervals overlap
+        Verdict::Improved,
+        Verdict::Added,
+        Verdict::Removed
+      ]
+    );
+    let error = comparison.check().unwrap_err();
+    assert_eq!(
+      error.to_string(),
+      "1 variant(s) regressed by more than 5.0% against baseline 'main'\n  Sort/Size-1: 100ns -> 120ns (+20.0%)"
+    );
+    assert!(comparison.render().ends_with("1 regressed; 1 improved; 2 unchanged; 1 added; 1 removed\n"));
+  }
+
+  #[test]
+  fn test_results_load_from_exported_files_and_baselines() {
+    let root = std::env::temp_dir().join(format!("bench_matrix_compare_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let write = |relative: &str, contents: &str| {
+      let path = root.join(relative);
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(&path, contents).unwrap();
+      path
+    };
+
+    let csv = write(
+      "results.csv",
+      "Backend,id,mean_ns,mean_lower_ns,mean_upper_ns\n\"a,b\",\"Io/Backend-a,b\",10.0,9.0,11.0\nEpoll,Io/Backend-Epoll,,,\n",
+    );
+    let json = write("results.json", r#"[{"Backend":"Uring","id":"Io/Backend-Uring","mean_ns":20.5,"mean_lower_ns":20.0,"mean_upper_ns":21.0}]"#);
+    let estimates = r#"{"mean":{"confidence_interval":{"confidence_level":0.95,"lower_bound":29.0,"upper_bound":31.0},"point_estimate":30.0},
+      "median":{"confidence_interval":{"confidence_level":0.95,"lower_bound":29.0,"upper_bound":31.0},"point_estimate":30.0},
+      "std_dev":{"confidence_interval":{"confidence_level":0.95,"lower_bound":1.0,"upper_bound":3.0},"point_estimate":2.0}}"#;
+    for (dir, full_id) in [("Io/Backend-Uring/main", "Io/Backend-Uring"), ("IoExtra/Backend-Uring/main", "IoExtra/Backend-Uring")] {
+      write(&format!("criterion/{}/benchmark.json", dir), &format!(r#"{{"full_id":"{}"}}"#, full_id));
+      write(&format!("criterion/{}/estimates.json", dir), estimates);
+    }
+
+    let from_csv = ResultSet::from_file(&csv).unwrap();
+    let from_json = ResultSet::from_file(&json).unwrap();
+    let from_baseline = ResultSet::from_baseline_dir("Io", "main", root.join("criterion")).unwrap();
+    let missing = ResultSet::from_baseline_dir("Io", "other", root.join("criterion"));
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(from_csv.means, vec![("Io/Backend-a,b".to_string(), estimate(10.0, 1.0))]);
+    assert_eq!(from_json.mean("Io/Backend-Uring"), Some(estimate(20.5, 0.5)));
+    assert_eq!(from_baseline.means, vec![("Io/Backend-Uring".to_string(), estimate(30.0, 1.0))]);
+    assert!(matches!(missing, Err(ExportError::NoResults { .. })));
+  }
+}
@@ -0,0 +1,116 @@
+use crate::params::{AbstractCombination, MatrixCellValue};
+
+/// A rule that matches a subset of benchmark combinations by their axis values.
+///
+/// A constraint is a list of conditions, each naming an axis and the values it may take.
+/// A combination matches the constraint when, for every condition, the combination's value
+/// on that axis is one of the listed values. Suites use constraints as exclusion rules to
+/// prune combinations that are invalid or uninteresting before any extraction or setup runs.
+///
+/// # Example
+/// ```
+/// # use bench_matrix::{AbstractCombination, Constraint, MatrixCellValue};
+/// let names = vec!["Backend".to_string(), "BlockSize".to_string()];
+/// let rule = Constraint::new()
+///   .when("Backend", ["Epoll"])
+///   .when("BlockSize", [4096u64, 8192u64]);
+///
+/// let combo = AbstractCombination {
+///   cells: vec![MatrixCellValue::Tag("Epoll".to_string()), MatrixCellValue::Unsigned(4096)],
+/// };
+/// assert_eq!(rule.matches(&combo, &names), Ok(true));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Constraint {
+  conditions: Vec<(String, Vec<MatrixCellValue>)>,
+}
+
+impl Constraint {
+  /// Creates an empty constraint. An empty constraint matches every combination.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a condition requiring the axis named `axis` to hold one of `values`.
+  pub fn when<V: Into<MatrixCellValue>>(mut self, axis: impl Into<String>, values: impl IntoIterator<Item = V>) -> Self {
+    self
+      .conditions
+      .push((axis.into(), values.into_iter().map(Into::into).collect()));
+    self
+  }
+
+  /// Returns the `(axis name, allowed values)` conditions of this constraint.
+  pub fn conditions(&self) -> &[(String, Vec<MatrixCellValue>)] {
+    &self.conditions
+  }
+
+  /// Checks that every axis referenced by this constraint exists in `param_names`.
+  pub fn check_axes(&self, param_names: &[String]) -> Result<(), String> {
+    for (axis, _) in &self.conditions {
+      if !param_names.iter().any(|name| name == axis) {
+        return Err(format!(
+          "Constraint references unknown axis '{}' (known axes: {})",
+          axis,
+          param_names.join(", ")
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Returns whether `combo` satisfies every condition of this constraint.
+  ///
+  /// `param_names` gives the axis name of each cell in `combo`, in order.
+  /// Returns an error if a condition names an axis that is not in `param_names`.
+  pub fn matches(&self, combo: &AbstractCombination, param_names: &[String]) -> Result<bool, String> {
+    for (axis, allowed) in &self.conditions {
+      let index = param_names.iter().position(|name| name == axis).ok_or_else(|| {
+        format!(
+          "Constraint references unknown axis '{}' (known axes: {})",
+          axis,
+          param_names.join(", ")
+        )
+      })?;
+      match combo.cells.get(index) {
+        Some(cell) if allowed.contains(cell) => {}
+        Some(_) => return Ok(false),
+        None => return Err(format!("No cell at index {} for axis '{}'", index, axis)),
+      }
+    }
+    Ok(true)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn names() -> Vec<String> {
+    vec!["Backend".to_string(), "BlockSize".to_string()]
+  }
+
+  fn combo(backend: &str, size: u64) -> AbstractCombination {
+    AbstractCombination {
+      cells: vec![MatrixCellValue::Tag(backend.to_string()), MatrixCellValue::Unsigned(size)],
+    }
+  }
+
+  #[test]
+  fn test_constraint_matches_all_conditions() {
+    let rule = Constraint::new().when("Backend", ["Epoll"]).when("BlockSize", [4096u64]);
+
+    assert_eq!(rule.matches(&combo("Epoll", 4096), &names()), Ok(true));
+    assert_eq!(rule.matches(&combo("Epoll", 512), &names()), Ok(false));
+    assert_eq!(rule.matches(&combo("Uring", 4096), &names()), Ok(false));
+    assert_eq!(Constraint::new().matches(&combo("Uring", 512), &names()), Ok(true));
+  }
+
+  #[test]
+  fn test_constraint_unknown_axis() {
+    let rule = Constraint::new().when("Backnd", ["Epoll"]);
+
+    assert!(rule.matches(&combo("Epoll", 4096), &names()).is_err());
+    let err = rule.check_axes(&names()).unwrap_err();
+    assert!(err.contains("'Backnd'"), "unexpected error: {}", err);
+  }
+}
@@ -1,43 +1,102 @@
 #![cfg(feature = "criterion_integration")]
 
-use super::{ExtractorFn, GlobalSetupFn, GlobalTeardownFn};
-use crate::generator::generate_combinations;
+use super::concurrency;
+use super::equivalence::{EquivalenceCheck, EquivalenceError, Output, OutputCollector};
+use super::measurements::{scale_throughput, VariantTotals};
+use super::executor::{BenchExecutor, BlockOnExecutor, CriterionExecutor};
+#[cfg(feature = "tokio")]
+use super::open_loop::OpenLoop;
+use super::runtimes::{runtime_axis_indices, RuntimeCache};
+use super::variant_settings::{apply_group_settings, VariantSettings};
+use super::{
+  criterion_benchmark_id, ConcurrencyFn, ConfigValidatorFn, CriterionGroupConfiguratorFn, ExtractorFn, GlobalSetupFn, GlobalTeardownFn,
+  MetricsFn, RuntimeFactoryFn, StateThroughputFn, ThroughputFn, VariantSettingsFn,
+};
+use crate::constraint::Constraint;
+use crate::filter::MatrixFilter;
+use crate::id::{IdFormat, IdTemplate};
+use crate::latency::LatencyRecorder;
+use crate::metrics::MetricsRecorder;
+use crate::plan::{list_mode_requested, MatrixPlan, PlanSource, SkipReason};
+use crate::report::SuiteReport;
+use crate::test_mode::{catch_panic, run_case, skip_outcome, TestReport};
+use crate::validate::{validate_plan, ValidationError};
 use crate::params::MatrixCellValue;
 
 use criterion::{
-  measurement::WallTime, AxisScale, Bencher, BenchmarkGroup, BenchmarkId, Criterion, PlotConfiguration,
+  measurement::WallTime, Bencher, BenchmarkGroup, Criterion,
   Throughput,
 };
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
 use std::time::Duration;
+#[cfg(feature = "tokio")]
 use tokio::runtime::Runtime;
 
-pub type AsyncSetupFn<S, Cfg, CtxT, SetupErr = String> =
-  fn(&Runtime, &Cfg) -> Pin<Box<dyn Future<Output = Result<(CtxT, S), SetupErr>> + Send>>;
+pub type AsyncSetupFn<S, Cfg, CtxT, SetupErr = String, E = BlockOnExecutor> =
+  fn(&E, &Cfg) -> Pin<Box<dyn Future<Output = Result<(CtxT, S), SetupErr>> + Send>>;
 pub type AsyncBenchmarkLogicFn<S, Cfg, CtxT> =
   fn(CtxT, S, &Cfg) -> Pin<Box<dyn Future<Output = (CtxT, S, Duration)> + Send>>;
-pub type AsyncTeardownFn<S, Cfg, CtxT> = fn(CtxT, S, &Runtime, &Cfg) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+pub type AsyncTeardownFn<S, Cfg, CtxT, E = BlockOnExecutor> = fn(CtxT, S, &E, &Cfg) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+/// Async counterpart of `GlobalSetupFn`, set with `global_setup_async`: per-configuration setup
+/// that needs the executor, such as starting a local server or opening a connection pool. The
+/// returned future is driven to completion on the variant's executor before any benchmark of
+/// the configuration runs.
+pub type AsyncGlobalSetupFn<Cfg, E = BlockOnExecutor> =
+  Box<dyn FnMut(&E, &Cfg) -> Pin<Box<dyn Future<Output = Result<(), String>>>>>;
+/// Async counterpart of `GlobalTeardownFn`, set with `global_teardown_async`.
+pub type AsyncGlobalTeardownFn<Cfg, E = BlockOnExecutor> =
+  Box<dyn FnMut(&E, &Cfg) -> Pin<Box<dyn Future<Output = Result<(), String>>>>>;
+/// Logic function that also records per-operation latencies, set with `recording_logic`.
+/// The recorder is a cheap handle that can be cloned into spawned tasks.
+pub type AsyncRecordingLogicFn<S, Cfg, CtxT> =
+  fn(CtxT, S, &Cfg, LatencyRecorder) -> Pin<Box<dyn Future<Output = (CtxT, S, Duration)> + Send>>;
 
-pub struct AsyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr = String, SetupErr = String> {
+/// Runs a parameter matrix of async benchmarks through Criterion on the executor `E`, usually
+/// inferred from the executor passed to `new`. The default, used only where the type is spelled
+/// out without `E`, is [`BlockOnExecutor`] regardless of features.
+pub struct AsyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr = String, SetupErr = String, E = BlockOnExecutor> {
   criterion: &'s mut Criterion<WallTime>,
-  runtime: &'s Runtime,
+  runtime: &'s E,
   suite_base_name: String,
   parameter_axes: Vec<Vec<MatrixCellValue>>,
   extractor_fn: ExtractorFn<Cfg, ExtErr>,
   parameter_names: Option<Vec<String>>,
   global_setup_fn: Option<GlobalSetupFn<Cfg>>,
-  setup_fn: AsyncSetupFn<S, Cfg, CtxT, SetupErr>,
+  async_global_setup_fn: Option<AsyncGlobalSetupFn<Cfg, E>>,
+  setup_fn: AsyncSetupFn<S, Cfg, CtxT, SetupErr, E>,
   benchmark_logic_fn: AsyncBenchmarkLogicFn<S, Cfg, CtxT>,
-  teardown_fn: AsyncTeardownFn<S, Cfg, CtxT>,
+  teardown_fn: AsyncTeardownFn<S, Cfg, CtxT, E>,
   global_teardown_fn: Option<GlobalTeardownFn<Cfg>>,
-  criterion_group_configurator: Option<Box<dyn for<'g> Fn(&mut BenchmarkGroup<'g, WallTime>)>>,
-  throughput_calculator: Option<Box<dyn Fn(&Cfg) -> Throughput>>,
+  async_global_teardown_fn: Option<AsyncGlobalTeardownFn<Cfg, E>>,
+  criterion_group_configurator: Option<CriterionGroupConfiguratorFn>,
+  throughput_calculator: Option<ThroughputFn<Cfg>>,
+  exclusions: Vec<Constraint>,
+  filters: Vec<MatrixFilter>,
+  config_validator: Option<ConfigValidatorFn<Cfg>>,
+  id_format: IdFormat,
+  group_by: Vec<String>,
+  x_axis: Option<String>,
+  variant_settings_fn: Option<VariantSettingsFn<Cfg>>,
+  secondary_throughputs: Vec<(String, ThroughputFn<Cfg>)>,
+  state_throughputs: Vec<(String, StateThroughputFn<S, Cfg, CtxT>)>,
+  metrics_fn: Option<MetricsFn<S, Cfg, CtxT>>,
+  concurrency_fn: Option<ConcurrencyFn<Cfg>>,
+  runtime_factory: Option<RuntimeFactoryFn<Cfg, E>>,
+  runtime_axes: Vec<String>,
+  recording_logic_fn: Option<AsyncRecordingLogicFn<S, Cfg, CtxT>>,
+  equivalence_check: Option<EquivalenceCheck<S, Cfg, CtxT>>,
+  #[cfg(feature = "tokio")]
+  open_loop: Option<OpenLoop<S, Cfg>>,
 }
 
-impl<'s, S, Cfg, CtxT, ExtErr, SetupErr> AsyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr, SetupErr>
+impl<'s, S, Cfg, CtxT, ExtErr, SetupErr, E> AsyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr, SetupErr, E>
 where
+  E: BenchExecutor,
   S: Send + 'static,
   Cfg: Clone + Debug + Send + Sync + 'static,
   CtxT: Send + 'static,
@@ -47,24 +106,24 @@ where
   #[allow(clippy::too_many_arguments)]
   pub fn new(
     criterion: &'s mut Criterion<WallTime>,
-    runtime: &'s Runtime,
+    runtime: &'s E,
     suite_base_name: String,
     parameter_names: Option<Vec<String>>,
     parameter_axes: Vec<Vec<MatrixCellValue>>,
     extractor_fn: ExtractorFn<Cfg, ExtErr>,
-    setup_fn: AsyncSetupFn<S, Cfg, CtxT, SetupErr>,
+    setup_fn: AsyncSetupFn<S, Cfg, CtxT, SetupErr, E>,
     benchmark_logic_fn: AsyncBenchmarkLogicFn<S, Cfg, CtxT>,
-    teardown_fn: AsyncTeardownFn<S, Cfg, CtxT>,
+    teardown_fn: AsyncTeardownFn<S, Cfg, CtxT, E>,
   ) -> Self {
-    if let Some(names) = &parameter_names {
-      if names.len() != parameter_axes.len() {
-        eprintln!(
-                "[BenchMatrix::Async] [WARN] Suite '{}': Mismatch between number of parameter_names ({}) and parameter_axes ({}). Parameter names will be ignored for ID generation.",
-                suite_base_name,
-                names.len(),
-                parameter_axes.len()
-            );
-      }
+    if let Some(names) = &parameter_names
+      && names.len() != parameter_axes.len()
+    {
+      eprintln!(
+              "[BenchMatrix::Async] [WARN] Suite '{}': Mismatch between number of parameter_names ({}) and parameter_axes ({}). Parameter names will be ignored for ID generation.",
+              suite_base_name,
+              names.len(),
+              parameter_axes.len()
+          );
     }
 
     Self {
@@ -75,12 +134,31 @@ where
       parameter_axes,
       extractor_fn,
       global_setup_fn: None,
+      async_global_setup_fn: None,
       setup_fn,
       benchmark_logic_fn,
       teardown_fn,
       global_teardown_fn: None,
+      async_global_teardown_fn: None,
       criterion_group_configurator: None,
       throughput_calculator: None,
+      exclusions: Vec::new(),
+      filters: Vec::new(),
+      config_validator: None,
+      id_format: IdFormat::default(),
+      group_by: Vec::new(),
+      x_axis: None,
+      variant_settings_fn: None,
+      secondary_throughputs: Vec::new(),
+      state_throughputs: Vec::new(),
+      metrics_fn: None,
+      concurrency_fn: None,
+      runtime_factory: None,
+      runtime_axes: Vec::new(),
+      recording_logic_fn: None,
+      equivalence_check: None,
+      #[cfg(feature = "tokio")]
+      open_loop: None,
     }
   }
 
@@ -109,21 +187,397 @@ where
     self
   }
 
+  /// Runs `f` on the variant's executor before the benchmarks of each configuration, e.g.
+  /// `|_rt, cfg| { let addr = cfg.addr.clone(); Box::pin(async move { start_server(addr).await }) }`.
+  /// When `global_setup` is also set, it runs first. An error skips the configuration, as for
+  /// `global_setup`.
+  pub fn global_setup_async(
+    mut self,
+    f: impl FnMut(&E, &Cfg) -> Pin<Box<dyn Future<Output = Result<(), String>>>> + 'static,
+  ) -> Self {
+    self.async_global_setup_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Runs `f` on the variant's executor after the benchmarks of each configuration, before
+  /// `global_teardown` if that is also set.
+  pub fn global_teardown_async(
+    mut self,
+    f: impl FnMut(&E, &Cfg) -> Pin<Box<dyn Future<Output = Result<(), String>>>> + 'static,
+  ) -> Self {
+    self.async_global_teardown_fn = Some(Box::new(f));
+    self
+  }
+
   pub fn configure_criterion_group(mut self, f: impl for<'g> Fn(&mut BenchmarkGroup<'g, WallTime>) + 'static) -> Self {
     self.criterion_group_configurator = Some(Box::new(f));
     self
   }
 
+  /// Sets the primary throughput, reported to Criterion and listed first (as `throughput`)
+  /// in the suite report returned by `run()`.
   pub fn throughput(mut self, f: impl Fn(&Cfg) -> Throughput + 'static) -> Self {
     self.throughput_calculator = Some(Box::new(f));
     self
   }
 
-  pub fn run(mut self) {
-    let abstract_combinations = generate_combinations(&self.parameter_axes);
+  /// Adds a further throughput dimension for the same measurement (e.g. messages/s next to
+  /// bytes/s). Criterion accepts only one throughput per benchmark, so secondary throughputs
+  /// appear only in the suite report returned by `run()`.
+  pub fn secondary_throughput(mut self, name: &str, f: impl Fn(&Cfg) -> Throughput + 'static) -> Self {
+    self.secondary_throughputs.push((name.to_string(), Box::new(f)));
+    self
+  }
+
+  /// Adds a throughput computed from the context and state at the end of each sample batch,
+  /// before teardown, e.g. the number of operations actually processed in that batch. The
+  /// amounts are summed over all batches and divided by the measured time. Like secondary
+  /// throughputs, these appear only in the suite report returned by `run()`.
+  pub fn throughput_from_state(mut self, name: &str, f: impl Fn(&CtxT, &S, &Cfg) -> Throughput + 'static) -> Self {
+    self.state_throughputs.push((name.to_string(), Box::new(f)));
+    self
+  }
+
+  /// Replaces the logic function given to `new` with one that also receives a
+  /// `LatencyRecorder`, for recording the latency of individual operations within an
+  /// iteration. The distribution (p50/p90/p99/p999/max) is reported per variant in the suite
+  /// report returned by `run()`, warm-up included.
+  pub fn recording_logic(mut self, f: AsyncRecordingLogicFn<S, Cfg, CtxT>) -> Self {
+    self.recording_logic_fn = Some(f);
+    self
+  }
+
+  /// Runs each variant on a runtime built by `f` instead of the runtime given to `new`, so the
+  /// runtime flavour and worker count can be swept as matrix axes. `axes` names the axes that
+  /// describe the runtime: one runtime is built per distinct combination of their values and
+  /// reused by every variant sharing it (with no axes, a single runtime is built). Requires
+  /// parameter names when `axes` is not empty.
+  pub fn runtime_factory(
+    mut self,
+    axes: impl IntoIterator<Item = impl Into<String>>,
+    f: impl Fn(&Cfg) -> E + 'static,
+  ) -> Self {
+    self.runtime_axes = axes.into_iter().map(Into::into).collect();
+    self.runtime_factory = Some(Box::new(f));
+    self
+  }
+
+  /// Checks that the implementations benchmarked along `axis` compute the same result. Before
+  /// timing starts, `run()` runs every variant once and takes `output` (the result, or a digest
+  /// of it) from the context and state after that iteration; variants differing only in `axis`
+  /// must produce equal outputs. Mismatches are reported as errors and the suite is then not
+  /// benchmarked. Requires parameter names.
+  pub fn check_equivalence<O: PartialEq + Debug + 'static>(
+    mut self,
+    axis: impl Into<String>,
+    output: impl Fn(&CtxT, &S, &Cfg) -> O + 'static,
+  ) -> Self {
+    self.equivalence_check = Some(EquivalenceCheck::new(axis.into(), output));
+    self
+  }
+
+  /// Records domain metrics after every measured iteration, e.g. a hit counter kept in the
+  /// context: `|ctx, _state, _cfg, metrics| metrics.record("hits", std::mem::take(&mut ctx.hits) as f64)`.
+  /// The hook runs outside the measured duration. Each metric is aggregated per variant (mean,
+  /// min, max, percentiles) in the suite report returned by `run()`.
+  pub fn collect_metrics(mut self, f: impl Fn(&mut CtxT, &S, &Cfg, &mut MetricsRecorder) + 'static) -> Self {
+    self.metrics_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Runs N copies of the benchmark logic at once, N being returned by `f` (usually read from
+  /// a matrix axis), to measure contention. Each copy gets its own context and state from the
+  /// setup function and runs on its own spawned task; the copies are released together by a barrier and
+  /// each sample batch is timed by the slowest copy's wall time. Throughputs are multiplied by
+  /// N, so they report the aggregate. `collect_metrics` runs once per copy at the end of each
+  /// sample batch rather than after every iteration.
+  pub fn concurrency(mut self, f: impl Fn(&Cfg) -> usize + 'static) -> Self {
+    self.concurrency_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Overrides Criterion settings per variant, e.g. fewer samples and a longer measurement
+  /// time for large payloads. The overrides are applied on top of the group's settings; the
+  /// variants after an overridden one continue in a fresh group of the same name, so Criterion's
+  /// HTML summary for that group covers only the last stretch. Settings Criterion would reject
+  /// (such as a sample size below 10) mark the variant as an invalid configuration.
+  pub fn variant_settings(mut self, f: impl Fn(&Cfg) -> VariantSettings + 'static) -> Self {
+    self.variant_settings_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Excludes every combination matching `constraint`. Excluded combinations are dropped
+  /// before extraction and global setup. Constraints refer to axes by name, so parameter
+  /// names must be set for exclusions to apply.
+  pub fn exclude(mut self, constraint: Constraint) -> Self {
+    self.exclusions.push(constraint);
+    self
+  }
+
+  /// Adds several exclusion constraints at once, e.g. the `exclusions` of a loaded matrix file.
+  pub fn exclusions(mut self, constraints: impl IntoIterator<Item = Constraint>) -> Self {
+    self.exclusions.extend(constraints);
+    self
+  }
+
+  /// Narrows (or overrides) axis values before any combination is generated, so filtered-out
+  /// variants never reach the extractor or global setup. The `BENCH_MATRIX_FILTER` environment
+  /// variable is applied on top of any filters set here.
+  pub fn filter(mut self, filter: MatrixFilter) -> Self {
+    self.filters.push(filter);
+    self
+  }
 
-    if abstract_combinations.len() == 0 {
-      let reason = if self.parameter_axes.is_empty() {
+  /// Sets how combinations are turned into benchmark IDs. The default, `IdFormat::Readable`,
+  /// can map distinct combinations to the same ID; such duplicates are refused rather than
+  /// handed to Criterion. `IdFormat::Reversible` never collides.
+  pub fn id_format(mut self, format: IdFormat) -> Self {
+    self.id_format = format;
+    self
+  }
+
+  /// Lays out benchmark IDs with `template`, e.g. `"{Backend}/{Size:bytes}/x{Threads}"`.
+  /// Shorthand for `.id_format(IdFormat::Template(template))`; requires parameter names.
+  pub fn id_template(self, template: IdTemplate) -> Self {
+    self.id_format(IdFormat::Template(template))
+  }
+
+  /// Splits the suite into one Criterion group per value of the given axes, named e.g.
+  /// `Suite/Backend-Uring`, so summary plots only compare related variants.
+  /// Requires parameter names.
+  pub fn group_by(mut self, axes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+    self.group_by = axes.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Uses the values of `axis` as the Criterion benchmark parameter, so Criterion's line
+  /// charts plot each group against it. The axis should be numeric. Requires parameter names.
+  pub fn x_axis(mut self, axis: impl Into<String>) -> Self {
+    self.x_axis = Some(axis.into());
+    self
+  }
+
+  /// Resolves the suite's matrix without running anything: applies filters and exclusion
+  /// constraints, runs the extractor over every combination and computes the benchmark IDs.
+  /// No setup function is called and nothing is registered with Criterion.
+  pub fn plan(&self) -> Result<MatrixPlan<Cfg>, String> {
+    if let Some(ref equivalence_check) = self.equivalence_check {
+      equivalence_check.axis_index(self.parameter_names.as_deref())?;
+    }
+    if self.runtime_factory.is_some() {
+      runtime_axis_indices(&self.runtime_axes, self.parameter_names.as_deref())?;
+    }
+    let check = |cfg: &Cfg| -> Result<(), String> {
+      if let Some(ref validator) = self.config_validator {
+        validator(cfg)?;
+      }
+      if let Some(ref settings_fn) = self.variant_settings_fn {
+        settings_fn(cfg).check().map_err(|e| format!("invalid variant settings: {}", e))?;
+      }
+      if let Some(ref concurrency_fn) = self.concurrency_fn
+        && concurrency_fn(cfg) == 0
+      {
+        return Err("concurrency must be at least 1".to_string());
+      }
+      #[cfg(feature = "tokio")]
+      if let Some(ref open_loop) = self.open_loop {
+        open_loop.check(cfg).map_err(|e| format!("invalid open-loop settings: {}", e))?;
+      }
+      Ok(())
+    };
+    PlanSource {
+      suite_name: &self.suite_base_name,
+      parameter_names: self.parameter_names.as_deref(),
+      parameter_axes: &self.parameter_axes,
+      filters: &self.filters,
+      exclusions: &self.exclusions,
+      extractor: &*self.extractor_fn,
+      config_validator: Some(&check),
+      id_format: &self.id_format,
+      group_by: &self.group_by,
+      x_axis: self.x_axis.as_deref(),
+    }
+    .plan()
+  }
+
+  /// Sets a check applied to every extracted configuration. Configurations it rejects are
+  /// reported by `validate()` and skipped by `run()`.
+  pub fn validate_config(mut self, f: impl Fn(&Cfg) -> Result<(), String> + 'static) -> Self {
+    self.config_validator = Some(Box::new(f));
+    self
+  }
+
+  /// Checks the whole matrix before anything runs and returns every problem at once:
+  /// parameter name/axis count disagreement, filter and constraint errors, extractor
+  /// failures, configurations rejected by `validate_config`, and duplicate benchmark IDs.
+  /// No setup function is called and nothing is registered with Criterion.
+  pub fn validate(&self) -> Result<(), ValidationError> {
+    validate_plan(
+      &self.suite_base_name,
+      self.parameter_names.as_deref(),
+      self.parameter_axes.len(),
+      self.plan(),
+    )
+  }
+
+  /// Prints the final benchmark IDs as a table, marking which combinations would be skipped
+  /// and why, and returns the plan. No setup function is called and nothing is registered
+  /// with Criterion. `run()` does this instead of benchmarking when `BENCH_MATRIX_LIST` is set.
+  pub fn list(&self) -> Result<MatrixPlan<Cfg>, String> {
+    let plan = self.plan().inspect_err(|e| {
+      eprintln!("[BenchMatrix::Async] [ERROR] Suite '{}': {}.", self.suite_base_name, e);
+    })?;
+    for warning in &plan.warnings {
+      eprintln!("[BenchMatrix::Async] [WARN] Suite '{}': {}", self.suite_base_name, warning);
+    }
+    println!("{}", plan.render_table());
+    Ok(plan)
+  }
+
+  /// Runs every combination once as a test instead of benchmarking it: global setup, setup,
+  /// one iteration of the logic (with `collect_metrics` and `throughput_from_state`), teardown
+  /// and global teardown, on the variant's executor and without Criterion. Each combination is
+  /// reported in libtest's format; extractor, validator and setup errors and panics are
+  /// failures. Call `assert_passed()` on the result from a `#[test]` function, with
+  /// `Criterion::default()` as the Criterion.
+  pub fn run_tests(mut self) -> TestReport {
+    let mut report = TestReport::new(&self.suite_base_name);
+    let plan = match self.plan() {
+      Ok(plan) => plan,
+      Err(e) => {
+        report.error = Some(e);
+        println!("{}", report.render_summary());
+        return report;
+      }
+    };
+    let runtime_axes = runtime_axis_indices(&self.runtime_axes, self.parameter_names.as_deref()).unwrap_or_default();
+    let mut runtimes = RuntimeCache::new(self.runtime, runtime_axes);
+
+    println!("\nrunning {} tests", plan.variants.len());
+    for variant in plan.variants {
+      let outcome = match variant.config {
+        Ok(ref cfg) => {
+          let runtime = runtimes.get(self.runtime_factory.as_ref(), &variant.combination, cfg);
+          run_case(|| self.run_once(runtime, cfg).map(|_| ()))
+        }
+        Err(ref reason) => skip_outcome(reason),
+      };
+      report.record(variant.full_id(), outcome);
+    }
+    println!("\n{}", report.render_summary());
+    report
+  }
+
+  /// Runs every runnable variant once, as `run_tests()` does, and checks that variants
+  /// differing only in the implementation axis set with `check_equivalence` produced equal
+  /// outputs. Variants that fail or panic are reported as unchecked. Succeeds without running
+  /// anything if `check_equivalence` is not set. `run()` calls this before timing starts and
+  /// skips the suite if it fails.
+  pub fn verify_equivalence(&mut self) -> Result<(), EquivalenceError> {
+    if self.equivalence_check.is_none() {
+      return Ok(());
+    }
+    let plan = self
+      .plan()
+      .map_err(|reason| EquivalenceError::suite_failure(&self.suite_base_name, reason))?;
+    self.verify_equivalence_of(&plan)
+  }
+
+  /// `verify_equivalence` for an already built plan, so `run()` plans the suite only once.
+  fn verify_equivalence_of(&mut self, plan: &MatrixPlan<Cfg>) -> Result<(), EquivalenceError> {
+    let Some(ref equivalence_check) = self.equivalence_check else {
+      return Ok(());
+    };
+    let axis_index = equivalence_check
+      .axis_index(self.parameter_names.as_deref())
+      .map_err(|reason| EquivalenceError::suite_failure(&self.suite_base_name, reason))?;
+    let names = self.parameter_names.clone().unwrap_or_default();
+    let runtime_axes = runtime_axis_indices(&self.runtime_axes, Some(&names)).unwrap_or_default();
+    let mut runtimes = RuntimeCache::new(self.runtime, runtime_axes);
+
+    let mut collector = OutputCollector::new(axis_index, &names);
+    for variant in &plan.variants {
+      if let Ok(ref cfg) = variant.config {
+        let runtime = runtimes.get(self.runtime_factory.as_ref(), &variant.combination, cfg);
+        let output = catch_panic(|| self.run_once(runtime, cfg)).map(|output| output.expect("check_equivalence is set"));
+        collector.record(variant.full_id(), &variant.combination, output);
+      }
+    }
+    collector.finish(&self.suite_base_name)
+  }
+
+  /// Runs global setup, setup, one iteration of the logic, teardown and global teardown on
+  /// `runtime`, returning the output taken for `check_equivalence`, if set.
+  fn run_once(&mut self, runtime: &E, cfg: &Cfg) -> Result<Option<Box<dyn Output>>, String> {
+    run_global_setup(self.global_setup_fn.as_mut(), self.async_global_setup_fn.as_mut(), runtime, cfg)
+      .map_err(|e| format!("global setup failed: {}", e))?;
+    let result = match runtime.block_on((self.setup_fn)(runtime, cfg)) {
+      Ok((user_ctx, setup_data_instance)) => {
+        let iteration = match self.recording_logic_fn {
+          Some(recording_logic) => recording_logic(user_ctx, setup_data_instance, cfg, LatencyRecorder::new()),
+          None => (self.benchmark_logic_fn)(user_ctx, setup_data_instance, cfg),
+        };
+        let (mut user_ctx, setup_data_instance, _) = runtime.block_on(iteration);
+        let output = self
+          .equivalence_check
+          .as_ref()
+          .map(|equivalence_check| equivalence_check.output(&user_ctx, &setup_data_instance, cfg));
+        if let Some(ref collect) = self.metrics_fn {
+          collect(&mut user_ctx, &setup_data_instance, cfg, &mut MetricsRecorder::default());
+        }
+        for (_, state_throughput) in &self.state_throughputs {
+          state_throughput(&user_ctx, &setup_data_instance, cfg);
+        }
+        runtime.block_on((self.teardown_fn)(user_ctx, setup_data_instance, runtime, cfg));
+        Ok(output)
+      }
+      Err(e) => Err(format!("setup failed: {:?}", e)),
+    };
+    let global_teardown = run_global_teardown(
+      self.global_teardown_fn.as_mut(),
+      self.async_global_teardown_fn.as_mut(),
+      runtime,
+      cfg,
+    )
+    .map_err(|e| format!("global teardown failed: {}", e));
+    result.and_then(|output| global_teardown.map(|()| output))
+  }
+
+  /// Runs every variant through Criterion and returns bench_matrix's own report of them.
+  /// In list mode, or if the suite cannot be planned, nothing runs and the report is empty.
+  pub fn run(mut self) -> SuiteReport {
+    let mut report = SuiteReport {
+      suite_name: self.suite_base_name.clone(),
+      parameter_names: self.parameter_names.clone(),
+      variants: Vec::new(),
+    };
+
+    if list_mode_requested() {
+      let _ = self.list();
+      return report;
+    }
+
+    let plan = match self.plan() {
+      Ok(plan) => plan,
+      Err(e) => {
+        eprintln!(
+          "[BenchMatrix::Async] [ERROR] Suite '{}': {}. Skipping this suite.",
+          self.suite_base_name, e
+        );
+        return report;
+      }
+    };
+
+    for warning in &plan.warnings {
+      eprintln!("[BenchMatrix::Async] [WARN] Suite '{}': {}", self.suite_base_name, warning);
+    }
+
+    // `plan()` has already checked the runtime axes.
+    let runtime_axes = runtime_axis_indices(&self.runtime_axes, self.parameter_names.as_deref()).unwrap_or_default();
+    let mut runtimes = RuntimeCache::new(self.runtime, runtime_axes);
+
+    if plan.variants.is_empty() {
+      let reason = if plan.parameter_axes.is_empty() {
         "no parameter axes defined"
       } else {
         "no combinations generated (e.g., an axis was empty)"
@@ -132,125 +586,261 @@ where
         "[BenchMatrix::Async] Suite '{}': {}. Nothing to run.",
         self.suite_base_name, reason
       );
-      return;
+      return report;
+    }
+
+    // Timings of implementations that disagree would compare wrong code, so none are taken.
+    if let Err(e) = self.verify_equivalence_of(&plan) {
+      eprintln!("[BenchMatrix::Async] [ERROR] {}", e);
+      eprintln!(
+        "[BenchMatrix::Async] [ERROR] Suite '{}': equivalence check failed. Skipping this suite.",
+        self.suite_base_name
+      );
+      return report;
     }
 
-    let total_variants = abstract_combinations.len();
+    let total_variants = plan.variants.len();
     let mut variants_run_count = 0;
     let mut variants_skipped_extraction = 0;
     let mut variants_skipped_global_setup = 0;
+    let mut variants_excluded = 0;
+    let mut variants_skipped_invalid = 0;
+    let mut variants_skipped_duplicate = 0;
 
-    let mut group = self.criterion.benchmark_group(&self.suite_base_name);
+    let mut variants = plan.variants.into_iter().peekable();
+    while let Some(group_name) = variants.peek().map(|variant| variant.group_name.clone()) {
+      let mut group = self.criterion.benchmark_group(&group_name);
 
-    if let Some(ref configurator) = self.criterion_group_configurator {
-        configurator(&mut group);
-    } else {
-        group
-            .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic))
-            .sample_size(10);
-    }
+      apply_group_settings(&mut group, self.criterion_group_configurator.as_ref());
+      let mut group_overridden = false;
 
-    for abstract_combo in abstract_combinations {
-      let concrete_config = match (self.extractor_fn)(&abstract_combo) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-          eprintln!(
-                        "[BenchMatrix::Async] [ERROR] Suite '{}', Combination ID '{}': Failed to extract concrete configuration: {:?}. Skipping this combination.",
-                        self.suite_base_name, abstract_combo.id_suffix(), e
-                    );
-          variants_skipped_extraction += 1;
-          continue;
-        }
-      };
+      while let Some(variant) = variants.next_if(|variant| variant.group_name == group_name) {
+        let full_id = variant.full_id();
+        let abstract_combo = variant.combination;
+        let concrete_config = match variant.config {
+          Ok(cfg) => cfg,
+          Err(SkipReason::Excluded) => {
+            variants_excluded += 1;
+            continue;
+          }
+          Err(SkipReason::Extraction(e)) => {
+            eprintln!(
+              "[BenchMatrix::Async] [ERROR] Suite '{}', Combination ID '{}': Failed to extract concrete configuration: {}. Skipping this combination.",
+              self.suite_base_name,
+              abstract_combo.id_suffix(),
+              e
+            );
+            variants_skipped_extraction += 1;
+            continue;
+          }
+          Err(SkipReason::InvalidConfig(e)) => {
+            eprintln!(
+              "[BenchMatrix::Async] [ERROR] Suite '{}', Combination ID '{}': Configuration rejected by validator: {}. Skipping this combination.",
+              self.suite_base_name,
+              abstract_combo.id_suffix(),
+              e
+            );
+            variants_skipped_invalid += 1;
+            continue;
+          }
+          Err(SkipReason::DuplicateId(first)) => {
+            eprintln!(
+              "[BenchMatrix::Async] [ERROR] Suite '{}', Benchmark ID '{}': ID already used by {}. Skipping this combination; consider IdFormat::Reversible.",
+              self.suite_base_name, full_id, first
+            );
+            variants_skipped_duplicate += 1;
+            continue;
+          }
+        };
 
-      if let Some(ref mut global_setup) = self.global_setup_fn {
-        if let Err(e) = global_setup(&concrete_config) {
+        let rt_for_iter = runtimes.get(self.runtime_factory.as_ref(), &abstract_combo, &concrete_config);
+        if let Err(e) = run_global_setup(
+          self.global_setup_fn.as_mut(),
+          self.async_global_setup_fn.as_mut(),
+          rt_for_iter,
+          &concrete_config,
+        ) {
           eprintln!(
-                        "[BenchMatrix::Async] [ERROR] Suite '{}', Config (ID '{}', Detail {:?}): Global setup failed: {}. Skipping benchmarks for this configuration.",
-                        self.suite_base_name, abstract_combo.id_suffix(), concrete_config, e
-                    );
+            "[BenchMatrix::Async] [ERROR] Suite '{}', Config (ID '{}', Detail {:?}): Global setup failed: {}. Skipping benchmarks for this configuration.",
+            self.suite_base_name,
+            abstract_combo.id_suffix(),
+            concrete_config,
+            e
+          );
           variants_skipped_global_setup += 1;
-          if let Some(ref mut global_teardown_on_setup_fail) = self.global_teardown_fn {
-            if let Err(td_err) = global_teardown_on_setup_fail(&concrete_config) {
-              eprintln!(
-                                "[BenchMatrix::Async] [WARN] Suite '{}', Config (ID '{}'): Global teardown after global setup failure also failed: {}",
-                                self.suite_base_name, abstract_combo.id_suffix(), td_err
-                            );
-            }
+          if let Err(td_err) = run_global_teardown(
+            self.global_teardown_fn.as_mut(),
+            self.async_global_teardown_fn.as_mut(),
+            rt_for_iter,
+            &concrete_config,
+          ) {
+            eprintln!(
+              "[BenchMatrix::Async] [WARN] Suite '{}', Config (ID '{}'): Global teardown after global setup failure also failed: {}",
+              self.suite_base_name,
+              abstract_combo.id_suffix(),
+              td_err
+            );
           }
           continue;
         }
-      }
 
-      let parameter_string = if let Some(names) = &self.parameter_names {
-        abstract_combo.id_suffix_with_names(names).strip_prefix('_').unwrap_or("").to_string()
-      } else {
-        abstract_combo.id_suffix().strip_prefix('_').unwrap_or("").to_string()
-      };
-      
-      let bench_id = BenchmarkId::from_parameter(&parameter_string);
+        if let Some(ref settings_fn) = self.variant_settings_fn {
+          // Criterion cannot unset a group setting, so the variants after an overridden one continue
+          // in a fresh group of the same name, which starts from the Criterion instance's settings.
+          if group_overridden {
+            group.finish();
+            group = self.criterion.benchmark_group(&group_name);
+            apply_group_settings(&mut group, self.criterion_group_configurator.as_ref());
+          }
+          group_overridden = settings_fn(&concrete_config).apply(&mut group);
+        }
+
+        let participant_count = self.concurrency_fn.as_ref().map(|concurrency_fn| concurrency_fn(&concrete_config));
+        let aggregate = |throughput: Throughput| match participant_count {
+          Some(participants) => scale_throughput(&throughput, participants),
+          None => throughput,
+        };
+
+        // Criterion applies the group's throughput to the benchmarks registered after it is set.
+        let mut declared_throughputs: Vec<(String, Throughput)> = Vec::new();
+        if let Some(ref throughput_calc) = self.throughput_calculator {
+          let throughput = aggregate(throughput_calc(&concrete_config));
+          group.throughput(throughput.clone());
+          declared_throughputs.push(("throughput".to_string(), throughput));
+        }
+        for (name, throughput_calc) in &self.secondary_throughputs {
+          declared_throughputs.push((name.clone(), aggregate(throughput_calc(&concrete_config))));
+        }
+        let variant_totals = RefCell::new(VariantTotals::default());
+        let totals = &variant_totals;
+        let state_throughput_fns = &self.state_throughputs;
+        let metrics_fn = self.metrics_fn.as_deref();
+        let variant_latency = LatencyRecorder::new();
+        let latency = &variant_latency;
 
-      let rt_for_iter = self.runtime;
-      let setup_fn_ptr = self.setup_fn;
-      let benchmark_logic_fn_ptr = self.benchmark_logic_fn;
-      let teardown_fn_ptr = self.teardown_fn;
+        let bench_id = criterion_benchmark_id(&variant.bench_id, variant.parameter.as_deref());
+
+        let setup_fn_ptr = self.setup_fn;
+        let benchmark_logic_fn_ptr = self.benchmark_logic_fn;
+        let teardown_fn_ptr = self.teardown_fn;
+        let recording_logic_fn_ptr = self.recording_logic_fn;
       
-      // Use `bench_with_input` to create a configurable benchmark.
-      // The `concrete_config` is passed as the "input" to the closure.
-      let bench_registration = group.bench_with_input(bench_id, &concrete_config, 
-        move |b: &mut Bencher<'_, WallTime>, cfg: &Cfg| {
-          b.to_async(rt_for_iter).iter_custom(|iters_count_hint| {
-            // The `cfg` from the closure is the specific config for this benchmark run.
-            let cfg_clone_per_sample = cfg.clone();
-            async move {
-              // Setup is done ONCE per sample batch.
-              let (mut user_ctx, mut setup_data_instance) = Box::pin((setup_fn_ptr)(rt_for_iter, &cfg_clone_per_sample))
-                .await
-                .unwrap_or_else(|e| {
-                  panic!(
-                    "[BenchMatrix::Async] PANIC in sample: Async setup_fn failed for config '{:?}': {:?}",
-                    cfg_clone_per_sample, e
+        // Use `bench_with_input` to create a configurable benchmark.
+        // The `concrete_config` is passed as the "input" to the closure.
+        group.bench_with_input(bench_id, &concrete_config, 
+          move |b: &mut Bencher<'_, WallTime>, cfg: &Cfg| {
+            b.to_async(CriterionExecutor(rt_for_iter)).iter_custom(|iters_count_hint| {
+              // The `cfg` from the closure is the specific config for this benchmark run.
+              let cfg_clone_per_sample = cfg.clone();
+              async move {
+                if let Some(participants) = participant_count {
+                  let mut participant_states = Vec::with_capacity(participants);
+                  for _ in 0..participants {
+                    let participant = Box::pin((setup_fn_ptr)(rt_for_iter, &cfg_clone_per_sample))
+                      .await
+                      .unwrap_or_else(|e| {
+                        panic!(
+                          "[BenchMatrix::Async] PANIC in sample: Async setup_fn failed for config '{:?}': {:?}",
+                          cfg_clone_per_sample, e
+                        )
+                      });
+                    participant_states.push(participant);
+                  }
+                  // One recorder per participant, merged afterwards, so recording never makes the
+                  // participants wait on each other.
+                  let participant_latencies: Vec<LatencyRecorder> = (0..participants).map(|_| LatencyRecorder::new()).collect();
+                  let recorders = participant_latencies.clone();
+                  let (participant_states, slowest_duration) = concurrency::run_tasks(
+                    rt_for_iter,
+                    participant_states,
+                    iters_count_hint,
+                    &cfg_clone_per_sample,
+                    move |participant, user_ctx, setup_data_instance, cfg: &Cfg| match recording_logic_fn_ptr {
+                      Some(recording_logic) => {
+                        recording_logic(user_ctx, setup_data_instance, cfg, recorders[participant].clone())
+                      }
+                      None => (benchmark_logic_fn_ptr)(user_ctx, setup_data_instance, cfg),
+                    },
                   )
-                });
+                  .await;
+                  for participant_latency in &participant_latencies {
+                    latency.merge(participant_latency);
+                  }
+
+                  for (mut user_ctx, setup_data_instance) in participant_states {
+                    if let Some(collect) = metrics_fn {
+                      collect(&mut user_ctx, &setup_data_instance, &cfg_clone_per_sample, &mut totals.borrow_mut().metrics);
+                    }
+                    for (index, (_, state_throughput)) in state_throughput_fns.iter().enumerate() {
+                      let throughput = state_throughput(&user_ctx, &setup_data_instance, &cfg_clone_per_sample);
+                      totals.borrow_mut().add_state_throughput(index, &throughput);
+                    }
+                    Box::pin((teardown_fn_ptr)(user_ctx, setup_data_instance, rt_for_iter, &cfg_clone_per_sample)).await;
+                  }
+
+                  totals.borrow_mut().record_run_batch(iters_count_hint, slowest_duration);
+                  return slowest_duration;
+                }
+
+                // Setup is done ONCE per sample batch.
+                let (mut user_ctx, mut setup_data_instance) = Box::pin((setup_fn_ptr)(rt_for_iter, &cfg_clone_per_sample))
+                  .await
+                  .unwrap_or_else(|e| {
+                    panic!(
+                      "[BenchMatrix::Async] PANIC in sample: Async setup_fn failed for config '{:?}': {:?}",
+                      cfg_clone_per_sample, e
+                    )
+                  });
+
+                let mut total_duration_for_sample_batch = Duration::new(0, 0);
+                for _i in 0..iters_count_hint {
+                  let logic_future = match recording_logic_fn_ptr {
+                    Some(recording_logic) => {
+                      recording_logic(user_ctx, setup_data_instance, &cfg_clone_per_sample, latency.clone())
+                    }
+                    None => (benchmark_logic_fn_ptr)(user_ctx, setup_data_instance, &cfg_clone_per_sample),
+                  };
+                  let (ctx_after_bench, s_after_bench, measured_duration) = Box::pin(logic_future).await;
+
+                  total_duration_for_sample_batch += measured_duration;
+                  user_ctx = ctx_after_bench;
+                  setup_data_instance = s_after_bench;
+
+                  if let Some(collect) = metrics_fn {
+                    collect(&mut user_ctx, &setup_data_instance, &cfg_clone_per_sample, &mut totals.borrow_mut().metrics);
+                  }
+                }
+
+                for (index, (_, state_throughput)) in state_throughput_fns.iter().enumerate() {
+                  let throughput = state_throughput(&user_ctx, &setup_data_instance, &cfg_clone_per_sample);
+                  totals.borrow_mut().add_state_throughput(index, &throughput);
+                }
 
-              let mut total_duration_for_sample_batch = Duration::new(0, 0);
-              for _i in 0..iters_count_hint {
-                let (ctx_after_bench, s_after_bench, measured_duration) = Box::pin((benchmark_logic_fn_ptr)(
+                // Teardown is done ONCE per sample batch.
+                Box::pin((teardown_fn_ptr)(
                   user_ctx,
                   setup_data_instance,
+                  rt_for_iter,
                   &cfg_clone_per_sample,
                 ))
                 .await;
 
-                total_duration_for_sample_batch += measured_duration;
-                user_ctx = ctx_after_bench;
-                setup_data_instance = s_after_bench;
+                totals.borrow_mut().record_run_batch(iters_count_hint, total_duration_for_sample_batch);
+                total_duration_for_sample_batch
               }
+            });
+          }
+        );
 
-              // Teardown is done ONCE per sample batch.
-              Box::pin((teardown_fn_ptr)(
-                user_ctx,
-                setup_data_instance,
-                rt_for_iter,
-                &cfg_clone_per_sample,
-              ))
-              .await;
-              
-              total_duration_for_sample_batch
-            }
-          });
-        }
-      );
-
-      // Now, configure the throughput on the returned benchmark object.
-      if let Some(ref throughput_calc) = self.throughput_calculator {
-        bench_registration.throughput(throughput_calc(&concrete_config));
-      }
 
-      variants_run_count += 1;
+        variants_run_count += 1;
 
-      if let Some(ref mut global_teardown) = self.global_teardown_fn {
-        if let Err(e) = global_teardown(&concrete_config) {
+        if let Err(e) = run_global_teardown(
+          self.global_teardown_fn.as_mut(),
+          self.async_global_teardown_fn.as_mut(),
+          rt_for_iter,
+          &concrete_config,
+        ) {
           eprintln!(
             "[BenchMatrix::Async] [WARN] Suite '{}', Config (ID '{}', Detail {:?}): Global teardown failed: {}",
             self.suite_base_name,
@@ -259,18 +849,36 @@ where
             e
           );
         }
+
+        // Variants deselected by Criterion's own command-line filter are never sampled.
+        let mut totals = variant_totals.into_inner();
+        totals.latency = variant_latency.summarize();
+        if totals.iterations > 0 {
+          let state_names: Vec<String> = self.state_throughputs.iter().map(|(name, _)| name.clone()).collect();
+          report
+            .variants
+            .push(totals.into_report(full_id, abstract_combo, &declared_throughputs, &state_names));
+        }
       }
+
+      group.finish();
     }
-    
-    group.finish();
 
-    if variants_skipped_extraction > 0 || variants_skipped_global_setup > 0 {
+    if variants_skipped_extraction > 0
+      || variants_skipped_invalid > 0
+      || variants_skipped_duplicate > 0
+      || variants_skipped_global_setup > 0
+      || variants_excluded > 0
+    {
       eprintln!(
-                "[BenchMatrix::Async] Suite '{}' summary: {} variants attempted, {} successfully run, {} skipped (extraction), {} skipped (global setup).",
+                "[BenchMatrix::Async] Suite '{}' summary: {} variants attempted, {} successfully run, {} excluded (constraints), {} skipped (extraction), {} skipped (invalid config), {} skipped (duplicate ID), {} skipped (global setup).",
                 self.suite_base_name,
                 total_variants,
                 variants_run_count,
+                variants_excluded,
                 variants_skipped_extraction,
+                variants_skipped_invalid,
+                variants_skipped_duplicate,
                 variants_skipped_global_setup
             );
     } else if variants_run_count > 0 {
@@ -279,5 +887,308 @@ where
         self.suite_base_name, variants_run_count
       );
     }
+
+    if !self.secondary_throughputs.is_empty()
+      || !self.state_throughputs.is_empty()
+      || self.metrics_fn.is_some()
+      || self.recording_logic_fn.is_some()
+    {
+      println!("{}", report.render());
+    }
+    report
   }
-}
\ No newline at end of file
+}
+
+/// Open-loop load generation, which relies on Tokio's timers and task spawning.
+#[cfg(feature = "tokio")]
+impl<'s, S, Cfg, CtxT, ExtErr, SetupErr> AsyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr, SetupErr, Runtime>
+where
+  S: Send + 'static,
+  Cfg: Clone + Debug + Send + Sync + 'static,
+  CtxT: Send + 'static,
+  ExtErr: Debug,
+  SetupErr: Debug,
+{
+  /// Sets the request function, target rate and duration used by `run_open_loop`. The rate
+  /// is checked for every configuration by `validate()`.
+  pub fn open_loop(mut self, open_loop: OpenLoop<S, Cfg>) -> Self {
+    self.open_loop = Some(open_loop);
+    self
+  }
+
+  /// Runs every variant as an open-loop load test instead of through Criterion, using the
+  /// settings given to `open_loop`: requests are issued on a fixed schedule at the variant's
+  /// target rate, regardless of how fast earlier ones complete, and each latency is measured
+  /// from the request's scheduled start, so queueing delays are not hidden (no coordinated
+  /// omission). Setup runs once per variant; the state is shared with every request through an
+  /// `Arc` and handed to teardown once all requests have finished. Returns the achieved rate
+  /// and latency distribution of each variant.
+  pub fn run_open_loop(mut self) -> SuiteReport {
+    let mut report = SuiteReport {
+      suite_name: self.suite_base_name.clone(),
+      parameter_names: self.parameter_names.clone(),
+      variants: Vec::new(),
+    };
+
+    if list_mode_requested() {
+      let _ = self.list();
+      return report;
+    }
+
+    let plan = match self.plan() {
+      Ok(plan) => plan,
+      Err(e) => {
+        eprintln!(
+          "[BenchMatrix::Async] [ERROR] Suite '{}': {}. Skipping this suite.",
+          self.suite_base_name, e
+        );
+        return report;
+      }
+    };
+
+    let Some(open_loop) = self.open_loop.take() else {
+      eprintln!(
+        "[BenchMatrix::Async] [ERROR] Suite '{}': run_open_loop requires open-loop settings (see `open_loop`). Skipping this suite.",
+        self.suite_base_name
+      );
+      return report;
+    };
+
+    for warning in &plan.warnings {
+      eprintln!("[BenchMatrix::Async] [WARN] Suite '{}': {}", self.suite_base_name, warning);
+    }
+
+    // `plan()` has already checked the runtime axes.
+    let runtime_axes = runtime_axis_indices(&self.runtime_axes, self.parameter_names.as_deref()).unwrap_or_default();
+    let mut runtimes = RuntimeCache::new(self.runtime, runtime_axes);
+
+    let total_variants = plan.variants.len();
+    let mut variants_run_count = 0;
+    let mut variants_skipped_extraction = 0;
+    let mut variants_skipped_global_setup = 0;
+    let mut variants_skipped_setup = 0;
+    let mut variants_excluded = 0;
+    let mut variants_skipped_invalid = 0;
+    let mut variants_skipped_duplicate = 0;
+
+    for variant in plan.variants {
+      let full_id = variant.full_id();
+      let abstract_combo = variant.combination;
+      let concrete_config = match variant.config {
+        Ok(cfg) => cfg,
+        Err(SkipReason::Excluded) => {
+          variants_excluded += 1;
+          continue;
+        }
+        Err(SkipReason::Extraction(e)) => {
+          eprintln!(
+            "[BenchMatrix::Async] [ERROR] Suite '{}', Combination ID '{}': Failed to extract concrete configuration: {}. Skipping this combination.",
+            self.suite_base_name,
+            abstract_combo.id_suffix(),
+            e
+          );
+          variants_skipped_extraction += 1;
+          continue;
+        }
+        Err(SkipReason::InvalidConfig(e)) => {
+          eprintln!(
+            "[BenchMatrix::Async] [ERROR] Suite '{}', Combination ID '{}': Configuration rejected by validator: {}. Skipping this combination.",
+            self.suite_base_name,
+            abstract_combo.id_suffix(),
+            e
+          );
+          variants_skipped_invalid += 1;
+          continue;
+        }
+        Err(SkipReason::DuplicateId(first)) => {
+          eprintln!(
+            "[BenchMatrix::Async] [ERROR] Suite '{}', Benchmark ID '{}': ID already used by {}. Skipping this combination; consider IdFormat::Reversible.",
+            self.suite_base_name, full_id, first
+          );
+          variants_skipped_duplicate += 1;
+          continue;
+        }
+      };
+
+      let runtime = runtimes.get(self.runtime_factory.as_ref(), &abstract_combo, &concrete_config);
+      if let Err(e) = run_global_setup(
+        self.global_setup_fn.as_mut(),
+        self.async_global_setup_fn.as_mut(),
+        runtime,
+        &concrete_config,
+      ) {
+        eprintln!(
+          "[BenchMatrix::Async] [ERROR] Suite '{}', Config (ID '{}', Detail {:?}): Global setup failed: {}. Skipping benchmarks for this configuration.",
+          self.suite_base_name,
+          abstract_combo.id_suffix(),
+          concrete_config,
+          e
+        );
+        variants_skipped_global_setup += 1;
+        if let Err(td_err) = run_global_teardown(
+          self.global_teardown_fn.as_mut(),
+          self.async_global_teardown_fn.as_mut(),
+          runtime,
+          &concrete_config,
+        ) {
+          eprintln!(
+            "[BenchMatrix::Async] [WARN] Suite '{}', Config (ID '{}'): Global teardown after global setup failure also failed: {}",
+            self.suite_base_name,
+            abstract_combo.id_suffix(),
+            td_err
+          );
+        }
+        continue;
+      }
+
+      match runtime.block_on((self.setup_fn)(runtime, &concrete_config)) {
+        Ok((user_ctx, setup_data_instance)) => {
+          println!("[BenchMatrix::Async] Running open loop '{}'...", full_id);
+          let shared_state = Arc::new(setup_data_instance);
+          let run = runtime.block_on(open_loop.drive(&shared_state, &concrete_config));
+          // Every spawned request has finished, so only a clone kept elsewhere by the request
+          // function can still hold the state.
+          match Arc::try_unwrap(shared_state) {
+            Ok(setup_data_instance) => {
+              runtime.block_on((self.teardown_fn)(user_ctx, setup_data_instance, runtime, &concrete_config))
+            }
+            Err(_) => eprintln!(
+              "[BenchMatrix::Async] [WARN] Suite '{}', Benchmark ID '{}': State still shared after the open-loop run. Skipping teardown.",
+              self.suite_base_name, full_id
+            ),
+          }
+          report.variants.push(run.into_report(full_id, abstract_combo.clone()));
+          variants_run_count += 1;
+        }
+        Err(e) => {
+          eprintln!(
+            "[BenchMatrix::Async] [ERROR] Suite '{}', Config (ID '{}', Detail {:?}): Setup failed: {:?}. Skipping this configuration.",
+            self.suite_base_name,
+            abstract_combo.id_suffix(),
+            concrete_config,
+            e
+          );
+          variants_skipped_setup += 1;
+        }
+      }
+
+      if let Err(e) = run_global_teardown(
+        self.global_teardown_fn.as_mut(),
+        self.async_global_teardown_fn.as_mut(),
+        runtime,
+        &concrete_config,
+      ) {
+        eprintln!(
+          "[BenchMatrix::Async] [WARN] Suite '{}', Config (ID '{}', Detail {:?}): Global teardown failed: {}",
+          self.suite_base_name,
+          abstract_combo.id_suffix(),
+          concrete_config,
+          e
+        );
+      }
+    }
+
+    if variants_run_count < total_variants {
+      eprintln!(
+        "[BenchMatrix::Async] Suite '{}' summary: {} variants attempted, {} successfully run, {} excluded (constraints), {} skipped (extraction), {} skipped (invalid config), {} skipped (duplicate ID), {} skipped (global setup), {} skipped (setup).",
+        self.suite_base_name,
+        total_variants,
+        variants_run_count,
+        variants_excluded,
+        variants_skipped_extraction,
+        variants_skipped_invalid,
+        variants_skipped_duplicate,
+        variants_skipped_global_setup,
+        variants_skipped_setup
+      );
+    }
+
+    println!("{}", report.render());
+    report
+  }
+}
+
+/// Runs the synchronous global setup, then the async one on `executor`.
+fn run_global_setup<Cfg, E: BenchExecutor>(
+  sync_hook: Option<&mut GlobalSetupFn<Cfg>>,
+  async_hook: Option<&mut AsyncGlobalSetupFn<Cfg, E>>,
+  executor: &E,
+  cfg: &Cfg,
+) -> Result<(), String> {
+  if let Some(global_setup) = sync_hook {
+    global_setup(cfg)?;
+  }
+  if let Some(global_setup) = async_hook {
+    executor.block_on(global_setup(executor, cfg))?;
+  }
+  Ok(())
+}
+
+/// Runs the async global teardown on `executor`, then the synchronous one. Both run even if
+/// the first fails; the first error is returned.
+fn run_global_teardown<Cfg, E: BenchExecutor>(
+  sync_hook: Option<&mut GlobalTeardownFn<Cfg>>,
+  async_hook: Option<&mut AsyncGlobalTeardownFn<Cfg, E>>,
+  executor: &E,
+  cfg: &Cfg,
+) -> Result<(), String> {
+  let async_result = match async_hook {
+    Some(global_teardown) => executor.block_on(global_teardown(executor, cfg)),
+    None => Ok(()),
+  };
+  let sync_result = match sync_hook {
+    Some(global_teardown) => global_teardown(cfg),
+    None => Ok(()),
+  };
+  async_result.and(sync_result)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::criterion_runner::executor::BlockOnExecutor;
+  use std::rc::Rc;
+
+  #[test]
+  fn test_global_hooks_run_in_order_and_teardown_runs_both() {
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let log = |name: &'static str| {
+      let calls = Rc::clone(&calls);
+      move || calls.borrow_mut().push(name)
+    };
+    let (sync_setup_log, async_setup_log) = (log("sync setup"), log("async setup"));
+    let (sync_teardown_log, async_teardown_log) = (log("sync teardown"), log("async teardown"));
+
+    let mut sync_setup: GlobalSetupFn<u32> = Box::new(move |_| {
+      sync_setup_log();
+      Ok(())
+    });
+    let mut async_setup: AsyncGlobalSetupFn<u32, BlockOnExecutor> = Box::new(move |_, cfg| {
+      async_setup_log();
+      let cfg = *cfg;
+      Box::pin(async move { if cfg > 0 { Ok(()) } else { Err("no port".to_string()) } })
+    });
+    let mut sync_teardown: GlobalTeardownFn<u32> = Box::new(move |_| {
+      sync_teardown_log();
+      Ok(())
+    });
+    let mut async_teardown: AsyncGlobalTeardownFn<u32, BlockOnExecutor> = Box::new(move |_, _| {
+      async_teardown_log();
+      Box::pin(async { Err("server already stopped".to_string()) })
+    });
+
+    assert!(run_global_setup(Some(&mut sync_setup), Some(&mut async_setup), &BlockOnExecutor, &8080).is_ok());
+    assert_eq!(
+      run_global_setup(Some(&mut sync_setup), Some(&mut async_setup), &BlockOnExecutor, &0),
+      Err("no port".to_string())
+    );
+    assert_eq!(
+      run_global_teardown(Some(&mut sync_teardown), Some(&mut async_teardown), &BlockOnExecutor, &0),
+      Err("server already stopped".to_string())
+    );
+    assert_eq!(
+      *calls.borrow(),
+      ["sync setup", "async setup", "sync setup", "async setup", "async teardown", "sync teardown"]
+    );
+  }
+}
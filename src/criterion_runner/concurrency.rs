@@ -0,0 +1,169 @@
+#![cfg(feature = "criterion_integration")]
+
+//! Running several copies of a variant's logic at once, for the suites' `concurrency` mode.
+//!
+//! Every participant gets its own context and state from the setup function. The participants
+//! are released together by a barrier, each runs the sample batch's iterations, and the batch
+//! is timed by the slowest participant's wall time. The logic is passed the participant's index,
+//! so per-participant resources such as latency recorders need no shared lock.
+
+use super::executor::{BenchExecutor, BoxedFuture};
+
+use std::future::Future;
+use std::sync::{Arc, Barrier, Mutex};
+use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
+
+/// Runs `logic` `iterations` times on one thread per participant and returns the participants'
+/// final context and state, in order, with the slowest participant's wall time. `logic` receives
+/// the participant's index first.
+pub(crate) fn run_threads<S, Cfg, CtxT>(
+  participants: Vec<(CtxT, S)>,
+  iterations: u64,
+  cfg: &Cfg,
+  logic: impl Fn(usize, CtxT, S, &Cfg) -> (CtxT, S, Duration) + Sync,
+) -> (Vec<(CtxT, S)>, Duration)
+where
+  S: Send,
+  Cfg: Sync,
+  CtxT: Send,
+{
+  let barrier = Barrier::new(participants.len());
+  let logic = &logic;
+  let barrier = &barrier;
+  let finished: Vec<(CtxT, S, Duration)> = std::thread::scope(|scope| {
+    let handles: Vec<_> = participants
+      .into_iter()
+      .enumerate()
+      .map(|(participant, (mut ctx, mut state))| {
+        scope.spawn(move || {
+          barrier.wait();
+          let start = Instant::now();
+          for _ in 0..iterations {
+            (ctx, state, _) = logic(participant, ctx, state, cfg);
+          }
+          (ctx, state, start.elapsed())
+        })
+      })
+      .collect();
+    handles
+      .into_iter()
+      .map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+      .collect()
+  });
+  collect_slowest(finished)
+}
+
+/// Runs `logic` `iterations` times on one task per participant, spawned through `executor`,
+/// and returns the participants' final context and state, in order, with the slowest
+/// participant's wall time. `logic` receives the participant's index first.
+pub(crate) async fn run_tasks<S, Cfg, CtxT, E, Fut>(
+  executor: &E,
+  participants: Vec<(CtxT, S)>,
+  iterations: u64,
+  cfg: &Cfg,
+  logic: impl Fn(usize, CtxT, S, &Cfg) -> Fut + Clone + Send + 'static,
+) -> (Vec<(CtxT, S)>, Duration)
+where
+  S: Send + 'static,
+  Cfg: Clone + Send + Sync + 'static,
+  CtxT: Send + 'static,
+  E: BenchExecutor + ?Sized,
+  Fut: Future<Output = (CtxT, S, Duration)> + Send + 'static,
+{
+  let gate = Arc::new(StartGate::new(participants.len()));
+  let tasks: Vec<BoxedFuture<(CtxT, S, Duration)>> = participants
+    .into_iter()
+    .enumerate()
+    .map(|(participant, (mut ctx, mut state))| {
+      let gate = Arc::clone(&gate);
+      let cfg = cfg.clone();
+      let logic = logic.clone();
+      Box::pin(async move {
+        gate.wait().await;
+        let start = Instant::now();
+        for _ in 0..iterations {
+          (ctx, state, _) = logic(participant, ctx, state, &cfg).await;
+        }
+        (ctx, state, start.elapsed())
+      }) as BoxedFuture<_>
+    })
+    .collect();
+  collect_slowest(executor.join_all(tasks).await)
+}
+
+/// An async barrier that releases every waiter once `participants` of them have arrived.
+/// Executor-agnostic, unlike Tokio's `Barrier`.
+struct StartGate {
+  state: Mutex<(usize, Vec<Waker>)>,
+}
+
+impl StartGate {
+  fn new(participants: usize) -> Self {
+    Self {
+      state: Mutex::new((participants, Vec::new())),
+    }
+  }
+
+  async fn wait(&self) {
+    {
+      let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+      state.0 = state.0.saturating_sub(1);
+      if state.0 == 0 {
+        state.1.drain(..).for_each(Waker::wake);
+      }
+    }
+    std::future::poll_fn(|cx| {
+      let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+      if state.0 == 0 {
+        Poll::Ready(())
+      } else {
+        state.1.push(cx.waker().clone());
+        Poll::Pending
+      }
+    })
+    .await
+  }
+}
+
+fn collect_slowest<S, CtxT>(finished: Vec<(CtxT, S, Duration)>) -> (Vec<(CtxT, S)>, Duration) {
+  let slowest = finished.iter().map(|(_, _, elapsed)| *elapsed).max().unwrap_or_default();
+  (finished.into_iter().map(|(ctx, state, _)| (ctx, state)).collect(), slowest)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  #[test]
+  fn test_participants_run_simultaneously() {
+    let arrived = AtomicUsize::new(0);
+    let participants: Vec<(usize, (u32, bool))> = (0..4).map(|index| (index, (0, false))).collect();
+    let (finished, _) = run_threads(participants, 3, &(), |participant, ctx, (count, overlapped), _cfg| {
+      assert_eq!(participant, ctx);
+      if count == 0 {
+        // Every participant has to be inside its first iteration at the same time; run one
+        // after another, the first would give up waiting for the others.
+        arrived.fetch_add(1, Ordering::SeqCst);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while arrived.load(Ordering::SeqCst) < 4 && Instant::now() < deadline {
+          std::thread::yield_now();
+        }
+      }
+      (ctx, (count + 1, overlapped || arrived.load(Ordering::SeqCst) == 4), Duration::ZERO)
+    });
+    assert_eq!(finished, (0..4).map(|index| (index, (3, true))).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn test_tasks_wait_for_each_other_without_a_runtime() {
+    use crate::criterion_runner::executor::BlockOnExecutor;
+
+    let participants: Vec<(usize, u32)> = (0..3).map(|index| (index, 0)).collect();
+    let (finished, _) = BlockOnExecutor.block_on(run_tasks(&BlockOnExecutor, participants, 2, &(), |_, ctx, count, _cfg: &()| {
+      std::future::ready((ctx, count + 1, Duration::ZERO))
+    }));
+    assert_eq!(finished, vec![(0, 2), (1, 2), (2, 2)]);
+  }
+}
@@ -0,0 +1,221 @@
+#![cfg(feature = "criterion_integration")]
+
+//! Checking that the implementations benchmarked along one axis compute the same result.
+//!
+//! With `check_equivalence`, the suites run every variant once before timing starts and take
+//! an output (or a digest of it) from the context and state after that iteration. Variants
+//! that only differ in the implementation axis form a group, and every output of a group must
+//! equal the group's first.
+
+use crate::filter::plain_text;
+use crate::params::{AbstractCombination, MatrixCellValue};
+
+use std::any::Any;
+use std::fmt::{self, Debug};
+
+/// An output taken by `check_equivalence`, compared with outputs of the same type.
+pub(crate) trait Output: Debug {
+  fn as_any(&self) -> &dyn Any;
+  fn equals(&self, other: &dyn Output) -> bool;
+}
+
+impl<T: PartialEq + Debug + 'static> Output for T {
+  fn as_any(&self) -> &dyn Any {
+    self
+  }
+
+  fn equals(&self, other: &dyn Output) -> bool {
+    other.as_any().downcast_ref::<T>() == Some(self)
+  }
+}
+
+type OutputFn<S, Cfg, CtxT> = Box<dyn Fn(&CtxT, &S, &Cfg) -> Box<dyn Output>>;
+
+/// The outputs of the variants sharing one set of values on the other axes, keyed by those
+/// values.
+type OutputGroup = (Vec<MatrixCellValue>, Vec<(String, Box<dyn Output>)>);
+
+/// The implementation axis and output function set with `check_equivalence`.
+pub(crate) struct EquivalenceCheck<S, Cfg, CtxT> {
+  axis: String,
+  output_fn: OutputFn<S, Cfg, CtxT>,
+}
+
+impl<S, Cfg, CtxT> EquivalenceCheck<S, Cfg, CtxT> {
+  pub(crate) fn new<O: PartialEq + Debug + 'static>(axis: String, output: impl Fn(&CtxT, &S, &Cfg) -> O + 'static) -> Self {
+    Self {
+      axis,
+      output_fn: Box::new(move |ctx, state, cfg| Box::new(output(ctx, state, cfg))),
+    }
+  }
+
+  /// Resolves the implementation axis to its index.
+  pub(crate) fn axis_index(&self, parameter_names: Option<&[String]>) -> Result<usize, String> {
+    let names = parameter_names.ok_or_else(|| "check_equivalence requires parameter names".to_string())?;
+    names
+      .iter()
+      .position(|name| *name == self.axis)
+      .ok_or_else(|| format!("Unknown implementation axis '{}' (known axes: {})", self.axis, names.join(", ")))
+  }
+
+  pub(crate) fn output(&self, ctx: &CtxT, state: &S, cfg: &Cfg) -> Box<dyn Output> {
+    (self.output_fn)(ctx, state, cfg)
+  }
+}
+
+/// A group of variants, differing only in the implementation axis, whose outputs differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputMismatch {
+  /// The values of the other axes shared by the group, e.g. `Size=64, Order=Reversed`.
+  pub group: String,
+  /// Each variant's full benchmark ID with its output (rendered with `{:?}`).
+  pub outputs: Vec<(String, String)>,
+}
+
+/// The problems found by `verify_equivalence`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EquivalenceError {
+  pub suite_name: String,
+  pub mismatches: Vec<OutputMismatch>,
+  /// Variants that produced no output (setup failure or panic), or the reason the suite could
+  /// not be checked at all, keyed by benchmark ID (the suite name for the latter).
+  pub failures: Vec<(String, String)>,
+}
+
+impl EquivalenceError {
+  /// The error for a suite that could not be checked at all.
+  pub(crate) fn suite_failure(suite_name: &str, reason: String) -> Self {
+    Self {
+      suite_name: suite_name.to_string(),
+      mismatches: Vec::new(),
+      failures: vec![(suite_name.to_string(), reason)],
+    }
+  }
+}
+
+impl fmt::Display for EquivalenceError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Suite '{}': {} output mismatch(es), {} variant(s) could not be checked",
+      self.suite_name,
+      self.mismatches.len(),
+      self.failures.len()
+    )?;
+    for mismatch in &self.mismatches {
+      write!(f, "\n  outputs differ for {}:", mismatch.group)?;
+      for (id, output) in &mismatch.outputs {
+        write!(f, "\n    {} => {}", id, output)?;
+      }
+    }
+    for (id, reason) in &self.failures {
+      write!(f, "\n  {}: {}", id, reason)?;
+    }
+    Ok(())
+  }
+}
+
+impl std::error::Error for EquivalenceError {}
+
+/// Collects the outputs of a suite's variants, grouped by their values on every axis but the
+/// implementation axis.
+pub(crate) struct OutputCollector<'n> {
+  axis_index: usize,
+  names: &'n [String],
+  groups: Vec<OutputGroup>,
+  failures: Vec<(String, String)>,
+}
+
+impl<'n> OutputCollector<'n> {
+  pub(crate) fn new(axis_index: usize, names: &'n [String]) -> Self {
+    Self {
+      axis_index,
+      names,
+      groups: Vec::new(),
+      failures: Vec::new(),
+    }
+  }
+
+  pub(crate) fn record(&mut self, id: String, combination: &AbstractCombination, output: Result<Box<dyn Output>, String>) {
+    let output = match output {
+      Ok(output) => output,
+      Err(reason) => {
+        self.failures.push((id, reason));
+        return;
+      }
+    };
+    let mut key = combination.cells.clone();
+    key.remove(self.axis_index);
+    match self.groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+      Some((_, outputs)) => outputs.push((id, output)),
+      None => self.groups.push((key, vec![(id, output)])),
+    }
+  }
+
+  pub(crate) fn finish(self, suite_name: &str) -> Result<(), EquivalenceError> {
+    let other_names: Vec<&String> = self
+      .names
+      .iter()
+      .enumerate()
+      .filter(|&(index, _)| index != self.axis_index)
+      .map(|(_, name)| name)
+      .collect();
+    let mismatches: Vec<OutputMismatch> = self
+      .groups
+      .into_iter()
+      .filter(|(_, outputs)| outputs.iter().any(|(_, output)| !output.equals(outputs[0].1.as_ref())))
+      .map(|(key, outputs)| OutputMismatch {
+        group: if key.is_empty() {
+          "all variants".to_string()
+        } else {
+          other_names
+            .iter()
+            .zip(&key)
+            .map(|(name, value)| format!("{}={}", name, plain_text(value)))
+            .collect::<Vec<_>>()
+            .join(", ")
+        },
+        outputs: outputs.into_iter().map(|(id, output)| (id, format!("{:?}", output))).collect(),
+      })
+      .collect();
+
+    if mismatches.is_empty() && self.failures.is_empty() {
+      Ok(())
+    } else {
+      Err(EquivalenceError {
+        suite_name: suite_name.to_string(),
+        mismatches,
+        failures: self.failures,
+      })
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_outputs_are_compared_within_groups() {
+    let names = vec!["Algorithm".to_string(), "Size".to_string()];
+    let check: EquivalenceCheck<Vec<u32>, (), ()> = EquivalenceCheck::new("Algorithm".to_string(), |_, state: &Vec<u32>, _| state.clone());
+    assert!(check.axis_index(Some(&names[1..])).is_err());
+    let combination = |algorithm: &str, size: u64| AbstractCombination {
+      cells: vec![MatrixCellValue::Tag(algorithm.to_string()), MatrixCellValue::Unsigned(size)],
+    };
+
+    let mut collector = OutputCollector::new(check.axis_index(Some(&names)).unwrap(), &names);
+    for (algorithm, size, output) in [("Quick", 2, vec![1, 2]), ("Merge", 2, vec![1, 2]), ("Quick", 3, vec![1, 2, 3]), ("Merge", 3, vec![2, 1, 3])] {
+      let id = format!("Sort/Algorithm-{}_Size-{}", algorithm, size);
+      collector.record(id, &combination(algorithm, size), Ok(check.output(&(), &output, &())));
+    }
+    collector.record("Sort/Algorithm-Heap_Size-3".to_string(), &combination("Heap", 3), Err("setup failed".to_string()));
+
+    let error = collector.finish("Sort").unwrap_err();
+    assert_eq!(error.mismatches.len(), 1);
+    assert_eq!(error.mismatches[0].group, "Size=3");
+    assert_eq!(error.mismatches[0].outputs[1], ("Sort/Algorithm-Merge_Size-3".to_string(), "[2, 1, 3]".to_string()));
+    assert_eq!(error.failures.len(), 1);
+    assert!(error.to_string().contains("Sort/Algorithm-Merge_Size-3 => [2, 1, 3]"), "{}", error);
+  }
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "criterion_integration")]
+
+//! The executors `AsyncBenchmarkSuite` can drive futures on.
+//!
+//! The suite only needs to block on a future and, in concurrency mode, to run several futures
+//! at once. Tokio's `Runtime` implements [`BenchExecutor`] behind the `tokio` feature;
+//! [`BlockOnExecutor`] needs no runtime at all and is the default type parameter in every feature
+//! set, so enabling `tokio` elsewhere in the dependency graph never changes a type. Other
+//! executors (smol, async-executor, ...) can be plugged in by implementing the trait.
+
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// A boxed future that can be moved to another thread, as passed to [`BenchExecutor::join_all`].
+pub type BoxedFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// An executor the async suite drives futures on.
+///
+/// # Example
+/// ```
+/// # use bench_matrix::{BenchExecutor, BlockOnExecutor};
+/// use std::future::Future;
+///
+/// // Delegates to another executor, e.g. one from smol or async-executor.
+/// struct Wrapped(BlockOnExecutor);
+///
+/// impl BenchExecutor for Wrapped {
+///   fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+///     self.0.block_on(future)
+///   }
+/// }
+///
+/// assert_eq!(Wrapped(BlockOnExecutor).block_on(async { 40 + 2 }), 42);
+/// ```
+pub trait BenchExecutor {
+  /// Runs `future` to completion, blocking the current thread.
+  fn block_on<T>(&self, future: impl Future<Output = T>) -> T;
+
+  /// Runs `futures` concurrently and returns their outputs in order. The default polls them
+  /// all from the awaiting task; executors with a thread pool should spawn each future so they
+  /// run in parallel.
+  fn join_all<T: Send + 'static>(&self, futures: Vec<BoxedFuture<T>>) -> impl Future<Output = Vec<T>> {
+    join_in_place(futures)
+  }
+}
+
+impl<E: BenchExecutor + ?Sized> BenchExecutor for &E {
+  fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+    (**self).block_on(future)
+  }
+
+  fn join_all<T: Send + 'static>(&self, futures: Vec<BoxedFuture<T>>) -> impl Future<Output = Vec<T>> {
+    (**self).join_all(futures)
+  }
+}
+
+#[cfg(feature = "tokio")]
+impl BenchExecutor for tokio::runtime::Runtime {
+  fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+    tokio::runtime::Runtime::block_on(self, future)
+  }
+
+  fn join_all<T: Send + 'static>(&self, futures: Vec<BoxedFuture<T>>) -> impl Future<Output = Vec<T>> {
+    let handles: Vec<_> = futures.into_iter().map(|future| self.spawn(future)).collect();
+    async move {
+      let mut outputs = Vec::with_capacity(handles.len());
+      for handle in handles {
+        match handle.await {
+          Ok(output) => outputs.push(output),
+          Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+      }
+      outputs
+    }
+  }
+}
+
+/// A minimal executor that polls futures on the calling thread, parking it while they are
+/// pending. It provides no timers or I/O, so it suits futures that do not depend on a runtime's
+/// services, or that bring their own (such as smol's `async-io`).
+///
+/// # Example
+/// ```
+/// # use bench_matrix::{BenchExecutor, BlockOnExecutor};
+/// assert_eq!(BlockOnExecutor.block_on(async { 40 + 2 }), 42);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockOnExecutor;
+
+impl BenchExecutor for BlockOnExecutor {
+  fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+      match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => return output,
+        Poll::Pending => thread::park(),
+      }
+    }
+  }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+  fn wake(self: Arc<Self>) {
+    self.0.unpark();
+  }
+}
+
+/// Polls every future from the current task until all have completed.
+async fn join_in_place<T>(futures: Vec<BoxedFuture<T>>) -> Vec<T> {
+  let mut pending: Vec<_> = futures.into_iter().map(Some).collect();
+  let mut outputs: Vec<Option<T>> = pending.iter().map(|_| None).collect();
+  std::future::poll_fn(|cx| {
+    let mut all_done = true;
+    for (slot, output) in pending.iter_mut().zip(outputs.iter_mut()) {
+      if let Some(future) = slot {
+        match future.as_mut().poll(cx) {
+          Poll::Ready(value) => {
+            *output = Some(value);
+            *slot = None;
+          }
+          Poll::Pending => all_done = false,
+        }
+      }
+    }
+    if all_done { Poll::Ready(()) } else { Poll::Pending }
+  })
+  .await;
+  outputs.into_iter().map(|output| output.expect("every future completed")).collect()
+}
+
+/// Adapts a [`BenchExecutor`] to Criterion's async benchmarking.
+pub(crate) struct CriterionExecutor<'e, E: ?Sized>(pub &'e E);
+
+impl<E: BenchExecutor + ?Sized> criterion::async_executor::AsyncExecutor for CriterionExecutor<'_, E> {
+  fn block_on<T>(&self, future: impl Future<Output = T>) -> T {
+    self.0.block_on(future)
+  }
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "criterion_integration")]
+
+//! Collection of bench_matrix's own per-variant measurements while Criterion samples a
+//! variant, and their conversion into a [`VariantReport`].
+
+use crate::latency::LatencySummary;
+use crate::metrics::MetricsRecorder;
+use crate::params::AbstractCombination;
+use crate::report::{ThroughputReport, VariantReport};
+
+use criterion::Throughput;
+use std::time::Duration;
+
+/// Totals accumulated over every sample batch of one variant. Criterion runs its warm-up
+/// through the same routine and gives no way to tell those batches apart, so the totals
+/// cover the whole run.
+#[derive(Debug, Default)]
+pub(crate) struct VariantTotals {
+  pub iterations: u64,
+  pub total_time: Duration,
+  /// Per state-based throughput function, the amount summed per unit over all batches.
+  state_amounts: Vec<Vec<(&'static str, f64)>>,
+  /// Values recorded by the suite's `collect_metrics` hook.
+  pub metrics: MetricsRecorder,
+  /// The distribution recorded through the variant's `LatencyRecorder`, if any.
+  pub latency: Option<LatencySummary>,
+}
+
+impl VariantTotals {
+  /// Records one batch of the run, warm-up or sample, of `iterations` iterations measured
+  /// at `elapsed`.
+  pub fn record_run_batch(&mut self, iterations: u64, elapsed: Duration) {
+    self.iterations += iterations;
+    self.total_time += elapsed;
+  }
+
+  /// Adds the amount reported by the `index`-th state-based throughput function for one batch.
+  pub fn add_state_throughput(&mut self, index: usize, throughput: &Throughput) {
+    if self.state_amounts.len() <= index {
+      self.state_amounts.resize_with(index + 1, Vec::new);
+    }
+    let amounts = &mut self.state_amounts[index];
+    for (unit, amount) in throughput_amounts(throughput) {
+      match amounts.iter_mut().find(|(existing, _)| *existing == unit) {
+        Some((_, total)) => *total += amount,
+        None => amounts.push((unit, amount)),
+      }
+    }
+  }
+
+  /// Builds the variant's report. `per_iteration` are the configuration-based throughputs
+  /// (primary first); `state_names` name the state-based throughput functions in order.
+  pub fn into_report(
+    self,
+    id: String,
+    combination: AbstractCombination,
+    per_iteration: &[(String, Throughput)],
+    state_names: &[String],
+  ) -> VariantReport {
+    let seconds = self.total_time.as_secs_f64();
+    let rate = |amount: f64| if seconds > 0.0 { amount / seconds } else { 0.0 };
+    let iterations = self.iterations as f64;
+
+    let mut throughputs = Vec::new();
+    for (name, throughput) in per_iteration {
+      for (unit, amount) in throughput_amounts(throughput) {
+        throughputs.push(ThroughputReport {
+          name: name.clone(),
+          unit: unit.to_string(),
+          per_iteration: amount,
+          per_second: rate(amount * iterations),
+        });
+      }
+    }
+    for (name, amounts) in state_names.iter().zip(self.state_amounts.iter().chain(std::iter::repeat(&Vec::new()))) {
+      for &(unit, total) in amounts {
+        throughputs.push(ThroughputReport {
+          name: name.clone(),
+          unit: unit.to_string(),
+          per_iteration: if iterations > 0.0 { total / iterations } else { 0.0 },
+          per_second: rate(total),
+        });
+      }
+    }
+
+    VariantReport {
+      id,
+      combination,
+      iterations: self.iterations,
+      total_time: self.total_time,
+      throughputs,
+      metrics: self.metrics.summarize(),
+      latency: self.latency,
+      open_loop: None,
+    }
+  }
+}
+
+/// Multiplies a per-participant throughput by the number of participants running at once.
+pub(crate) fn scale_throughput(throughput: &Throughput, participants: usize) -> Throughput {
+  let n = participants as u64;
+  match *throughput {
+    Throughput::Bits(amount) => Throughput::Bits(amount * n),
+    Throughput::Bytes(amount) => Throughput::Bytes(amount * n),
+    Throughput::BytesDecimal(amount) => Throughput::BytesDecimal(amount * n),
+    Throughput::Elements(amount) => Throughput::Elements(amount * n),
+    Throughput::ElementsAndBytes { elements, bytes } => Throughput::ElementsAndBytes {
+      elements: elements * n,
+      bytes: bytes * n,
+    },
+  }
+}
+
+/// Splits a Criterion throughput into `(unit, amount)` pairs.
+fn throughput_amounts(throughput: &Throughput) -> Vec<(&'static str, f64)> {
+  match *throughput {
+    Throughput::Bits(n) => vec![("bits", n as f64)],
+    Throughput::Bytes(n) | Throughput::BytesDecimal(n) => vec![("bytes", n as f64)],
+    Throughput::Elements(n) => vec![("elements", n as f64)],
+    Throughput::ElementsAndBytes { elements, bytes } => vec![("elements", elements as f64), ("bytes", bytes as f64)],
+  }
+}
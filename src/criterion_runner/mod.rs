@@ -1,6 +1,9 @@
 #![cfg(feature = "criterion_integration")]
 
+use crate::metrics::MetricsRecorder;
 use crate::params::AbstractCombination;
+use variant_settings::VariantSettings;
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId, Throughput};
 
 // --- Common User-Provided Function Signature Types ---
 // These are types that might be used by both async and sync suites,
@@ -32,7 +35,57 @@ pub type GlobalSetupFn<Cfg> = Box<dyn FnMut(&Cfg) -> Result<(), String>>;
 /// Used for cleaning up any resources initialized by `GlobalSetupFn`.
 pub type GlobalTeardownFn<Cfg> = Box<dyn FnMut(&Cfg) -> Result<(), String>>;
 
+/// Function to check a resolved configuration (`Cfg`) before anything runs.
+///
+/// Returning `Err` marks the configuration as invalid: `validate()` reports it as a problem
+/// and `run()` skips the corresponding variant.
+pub type ConfigValidatorFn<Cfg> = Box<dyn Fn(&Cfg) -> Result<(), String>>;
+
+/// Closure used to customize the Criterion `BenchmarkGroup` created for a suite
+/// (sample size, measurement time, plot configuration, etc.).
+pub type CriterionGroupConfiguratorFn = Box<dyn for<'g> Fn(&mut BenchmarkGroup<'g, WallTime>)>;
+
+/// Function to compute the Criterion `Throughput` reported for a resolved configuration (`Cfg`).
+pub type ThroughputFn<Cfg> = Box<dyn Fn(&Cfg) -> Throughput>;
+
+/// Function to compute a throughput from the user context (`CtxT`) and state (`S`) at the end
+/// of a sample batch, e.g. the number of operations actually processed during that batch.
+pub type StateThroughputFn<S, Cfg, CtxT> = Box<dyn Fn(&CtxT, &S, &Cfg) -> Throughput>;
+
+/// Function called after every measured iteration to record domain metrics (cache hit rate,
+/// retries, queue depth, ...) from the user context (`CtxT`) and state (`S`). The context is
+/// mutable so that per-iteration counters can be reset.
+pub type MetricsFn<S, Cfg, CtxT> = Box<dyn Fn(&mut CtxT, &S, &Cfg, &mut MetricsRecorder)>;
+
+/// Function returning how many copies of the benchmark logic run at once for a resolved
+/// configuration (`Cfg`), for measuring contention.
+pub type ConcurrencyFn<Cfg> = Box<dyn Fn(&Cfg) -> usize>;
+
+/// Function building the executor (e.g. Tokio runtime) an async variant runs on from its
+/// resolved configuration (`Cfg`), e.g. to sweep the runtime flavour and worker count as axes.
+pub type RuntimeFactoryFn<Cfg, E = executor::BlockOnExecutor> = Box<dyn Fn(&Cfg) -> E>;
+
+/// Function to compute per-variant Criterion settings (sample size, measurement time, etc.)
+/// for a resolved configuration (`Cfg`), applied on top of the group's settings.
+pub type VariantSettingsFn<Cfg> = Box<dyn Fn(&Cfg) -> VariantSettings>;
+
+/// Builds the Criterion ID of a planned variant: the x-axis value becomes the benchmark
+/// parameter (so Criterion can draw line charts over it) and the remaining axes the function name.
+pub(crate) fn criterion_benchmark_id(bench_id: &str, parameter: Option<&str>) -> BenchmarkId {
+  match parameter {
+    Some(parameter) if bench_id.is_empty() => BenchmarkId::from_parameter(parameter),
+    Some(parameter) => BenchmarkId::new(bench_id.to_string(), parameter),
+    None => BenchmarkId::from_parameter(bench_id),
+  }
+}
 
 // Declare the submodules for async and sync benchmark suites.
 pub mod async_suite;
-pub mod sync_suite;
\ No newline at end of file
+mod concurrency;
+pub mod equivalence;
+pub mod executor;
+mod measurements;
+pub mod open_loop;
+mod runtimes;
+pub mod sync_suite;
+pub mod variant_settings;
\ No newline at end of file
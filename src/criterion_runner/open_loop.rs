@@ -0,0 +1,221 @@
+#![cfg(all(feature = "criterion_integration", feature = "tokio"))]
+
+//! Open-loop load generation for `AsyncBenchmarkSuite::run_open_loop`.
+//!
+//! Closed-loop iteration issues the next request only once the previous one completed, so a
+//! slow response delays every later request and hides the queueing a real client would see
+//! (coordinated omission). An open-loop run instead issues requests on a fixed schedule at the
+//! target rate, keeps many of them in flight, and measures each latency from the time the
+//! request was *scheduled* to start rather than the time it was actually sent.
+
+use crate::latency::LatencyHistogram;
+use crate::params::AbstractCombination;
+use crate::report::{OpenLoopSummary, VariantReport};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::Instant;
+
+/// Function issuing one request against the shared state set up for the variant. The returned
+/// future is spawned onto the suite's runtime, so it must own what it needs (clone the `Arc`
+/// and the relevant configuration fields into it).
+pub type OpenLoopRequestFn<S, Cfg> = fn(Arc<S>, &Cfg) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Function returning the target request rate, in requests per second, of a configuration.
+pub type OpenLoopRateFn<Cfg> = Box<dyn Fn(&Cfg) -> f64>;
+
+/// Open-loop settings of an `AsyncBenchmarkSuite`, set with `open_loop` and used by
+/// `run_open_loop`.
+pub struct OpenLoop<S, Cfg> {
+  request_fn: OpenLoopRequestFn<S, Cfg>,
+  rate_fn: OpenLoopRateFn<Cfg>,
+  duration: Duration,
+  max_in_flight: usize,
+}
+
+impl<S, Cfg> OpenLoop<S, Cfg> {
+  /// Issues `request_fn` at the rate returned by `rate` (usually read from a matrix axis) for
+  /// 10 seconds per variant, with at most 10,000 requests in flight.
+  pub fn new(request_fn: OpenLoopRequestFn<S, Cfg>, rate: impl Fn(&Cfg) -> f64 + 'static) -> Self {
+    Self {
+      request_fn,
+      rate_fn: Box::new(rate),
+      duration: Duration::from_secs(10),
+      max_in_flight: 10_000,
+    }
+  }
+
+  /// Sets how long requests are issued for each variant.
+  pub fn duration(mut self, duration: Duration) -> Self {
+    self.duration = duration;
+    self
+  }
+
+  /// Caps the number of requests in flight. When the cap is reached, the next request waits
+  /// for a slot; its latency still counts from its scheduled start, so the wait is reported.
+  pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+    self.max_in_flight = max_in_flight;
+    self
+  }
+
+  /// Checks the settings and the target rate of `cfg`.
+  pub(crate) fn check(&self, cfg: &Cfg) -> Result<(), String> {
+    let rate = (self.rate_fn)(cfg);
+    if !rate.is_finite() || rate <= 0.0 {
+      return Err(format!("target rate must be a positive number of requests per second, got {}", rate));
+    }
+    if self.duration.is_zero() {
+      return Err("duration must be greater than zero".to_string());
+    }
+    if self.max_in_flight == 0 {
+      return Err("max_in_flight must be at least 1".to_string());
+    }
+    Ok(())
+  }
+}
+
+/// The outcome of driving one variant.
+pub(crate) struct OpenLoopRun {
+  pub target_rate: f64,
+  /// Latencies of the completed requests, measured from their scheduled start.
+  pub histogram: LatencyHistogram,
+  pub sent: u64,
+  pub completed: u64,
+  /// Requests whose future panicked.
+  pub failed: u64,
+  /// From the first scheduled start until the last request finished.
+  pub elapsed: Duration,
+}
+
+impl OpenLoopRun {
+  /// Builds the variant's report. Completed requests count as iterations of the run's
+  /// wall-clock duration.
+  pub(crate) fn into_report(self, id: String, combination: AbstractCombination) -> VariantReport {
+    let seconds = self.elapsed.as_secs_f64();
+    VariantReport {
+      id,
+      combination,
+      iterations: self.completed,
+      total_time: self.elapsed,
+      throughputs: Vec::new(),
+      metrics: Vec::new(),
+      latency: self.histogram.summarize(),
+      open_loop: Some(OpenLoopSummary {
+        target_rate: self.target_rate,
+        achieved_rate: if seconds > 0.0 { self.completed as f64 / seconds } else { 0.0 },
+        sent: self.sent,
+        completed: self.completed,
+        failed: self.failed,
+      }),
+    }
+  }
+
+  fn record(&mut self, result: Result<Duration, JoinError>) {
+    match result {
+      Ok(latency) => {
+        self.histogram.record(latency);
+        self.completed += 1;
+      }
+      Err(_) => self.failed += 1,
+    }
+  }
+}
+
+impl<S, Cfg> OpenLoop<S, Cfg> {
+  /// Issues requests at the target rate of `cfg` for the configured duration and waits for the
+  /// outstanding ones. Must be called from within a Tokio runtime, onto which the requests are
+  /// spawned. Expects `check` to have accepted `cfg`.
+  pub(crate) async fn drive(&self, state: &Arc<S>, cfg: &Cfg) -> OpenLoopRun {
+    let rate = (self.rate_fn)(cfg);
+    let total = ((self.duration.as_secs_f64() * rate).round() as u64).max(1);
+    let mut in_flight: JoinSet<Duration> = JoinSet::new();
+    let mut run = OpenLoopRun {
+      target_rate: rate,
+      histogram: LatencyHistogram::default(),
+      sent: 0,
+      completed: 0,
+      failed: 0,
+      elapsed: Duration::ZERO,
+    };
+
+    let start = Instant::now();
+    while run.sent < total {
+      if in_flight.len() >= self.max_in_flight {
+        if let Some(result) = in_flight.join_next().await {
+          run.record(result);
+        }
+        continue;
+      }
+      let scheduled = start + Duration::from_secs_f64(run.sent as f64 / rate);
+      if Instant::now() < scheduled {
+        // Collect completions while waiting, so finished requests do not pile up.
+        tokio::select! {
+          biased;
+          Some(result) = in_flight.join_next(), if !in_flight.is_empty() => {
+            run.record(result);
+            continue;
+          }
+          _ = tokio::time::sleep_until(scheduled) => {}
+        }
+      }
+      let request = (self.request_fn)(Arc::clone(state), cfg);
+      in_flight.spawn(async move {
+        request.await;
+        scheduled.elapsed()
+      });
+      run.sent += 1;
+    }
+    while let Some(result) = in_flight.join_next().await {
+      run.record(result);
+    }
+    run.elapsed = start.elapsed();
+    run
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sleep_request(_state: Arc<()>, cfg: &Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(tokio::time::sleep(*cfg))
+  }
+
+  /// A runtime whose clock only advances when every task is waiting on a timer, so the
+  /// schedule and the latencies below are exact rather than subject to the machine's load.
+  fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+      .enable_time()
+      .start_paused(true)
+      .build()
+      .unwrap()
+  }
+
+  #[test]
+  fn test_issues_requests_at_target_rate() {
+    let open_loop = OpenLoop::new(sleep_request, |_| 1000.0).duration(Duration::from_millis(100));
+    let run = runtime().block_on(open_loop.drive(&Arc::new(()), &Duration::from_millis(1)));
+    assert_eq!((run.sent, run.completed, run.failed), (100, 100, 0));
+    // The last request is scheduled at 99ms and takes 1ms.
+    assert_eq!(run.elapsed, Duration::from_millis(100));
+    assert_eq!(run.histogram.summarize().unwrap().max, Duration::from_millis(1));
+  }
+
+  #[test]
+  fn test_latency_includes_queueing_behind_slow_requests() {
+    // One slot, 5ms requests, scheduled every 1ms: requests fall further and further behind
+    // schedule, which the latency from the scheduled start must show.
+    let open_loop = OpenLoop::new(sleep_request, |_| 1000.0)
+      .duration(Duration::from_millis(50))
+      .max_in_flight(1);
+    let run = runtime().block_on(open_loop.drive(&Arc::new(()), &Duration::from_millis(5)));
+    assert_eq!(run.completed, 50);
+    // Request k is scheduled at k ms but starts at 5k ms, so it completes 4k + 5 ms late.
+    let summary = run.histogram.summarize().unwrap();
+    assert_eq!(summary.min, Duration::from_millis(5));
+    assert_eq!(summary.max, Duration::from_millis(201));
+  }
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "criterion_integration")]
+
+//! Per-variant executors for `AsyncBenchmarkSuite::runtime_factory`.
+
+use super::RuntimeFactoryFn;
+use crate::params::{AbstractCombination, MatrixCellValue};
+
+use std::collections::HashMap;
+
+/// Resolves the axes that describe the runtime configuration to their indices.
+pub(crate) fn runtime_axis_indices(axes: &[String], parameter_names: Option<&[String]>) -> Result<Vec<usize>, String> {
+  if axes.is_empty() {
+    return Ok(Vec::new());
+  }
+  let names = parameter_names.ok_or_else(|| "runtime_factory axes require parameter names".to_string())?;
+  axes
+    .iter()
+    .map(|axis| {
+      names
+        .iter()
+        .position(|name| name == axis)
+        .ok_or_else(|| format!("Unknown runtime axis '{}' (known axes: {})", axis, names.join(", ")))
+    })
+    .collect()
+}
+
+/// The executor of each variant: the suite's executor, or one built by the factory per distinct
+/// combination of the runtime axes' values and reused by every variant sharing them.
+pub(crate) struct RuntimeCache<'r, E> {
+  default: &'r E,
+  axis_indices: Vec<usize>,
+  built: HashMap<Vec<MatrixCellValue>, E>,
+}
+
+impl<'r, E> RuntimeCache<'r, E> {
+  pub(crate) fn new(default: &'r E, axis_indices: Vec<usize>) -> Self {
+    Self {
+      default,
+      axis_indices,
+      built: HashMap::new(),
+    }
+  }
+
+  /// Returns the executor for a variant, building it with `factory` on first use.
+  pub(crate) fn get<Cfg>(
+    &mut self,
+    factory: Option<&RuntimeFactoryFn<Cfg, E>>,
+    combination: &AbstractCombination,
+    cfg: &Cfg,
+  ) -> &E {
+    match factory {
+      Some(factory) => self
+        .built
+        .entry(combination.select(&self.axis_indices).cells)
+        .or_insert_with(|| factory(cfg)),
+      None => self.default,
+    }
+  }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  #[test]
+  fn test_runtimes_are_shared_per_runtime_axis_value() {
+    let default = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    let names = vec!["Workers".to_string(), "Size".to_string()];
+    let indices = runtime_axis_indices(&["Workers".to_string()], Some(&names)).unwrap();
+    assert!(runtime_axis_indices(&["Flavor".to_string()], Some(&names)).is_err());
+
+    let builds = std::rc::Rc::new(Cell::new(0));
+    let counter = builds.clone();
+    let factory: RuntimeFactoryFn<u64, tokio::runtime::Runtime> = Box::new(move |workers| {
+      counter.set(counter.get() + 1);
+      tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(*workers as usize)
+        .build()
+        .unwrap()
+    });
+    let combination = |workers: u64, size: u64| AbstractCombination {
+      cells: vec![MatrixCellValue::Unsigned(workers), MatrixCellValue::Unsigned(size)],
+    };
+
+    let mut cache = RuntimeCache::new(&default, indices);
+    for (workers, size) in [(1, 64), (1, 512), (2, 64), (2, 512)] {
+      let runtime = cache.get(Some(&factory), &combination(workers, size), &workers);
+      assert_eq!(runtime.metrics().num_workers(), workers as usize);
+    }
+    assert_eq!(builds.get(), 2);
+    assert!(std::ptr::eq(cache.get(None::<&RuntimeFactoryFn<u64, tokio::runtime::Runtime>>, &combination(1, 64), &1), &default));
+  }
+}
@@ -1,19 +1,37 @@
 #![cfg(feature = "criterion_integration")]
 
-use super::{ExtractorFn, GlobalSetupFn, GlobalTeardownFn};
-use crate::generator::generate_combinations;
+use super::concurrency;
+use super::equivalence::{EquivalenceCheck, EquivalenceError, Output, OutputCollector};
+use super::measurements::{scale_throughput, VariantTotals};
+use super::variant_settings::{apply_group_settings, VariantSettings};
+use super::{
+  criterion_benchmark_id, ConcurrencyFn, ConfigValidatorFn, CriterionGroupConfiguratorFn, ExtractorFn, GlobalSetupFn, GlobalTeardownFn,
+  MetricsFn, StateThroughputFn, ThroughputFn, VariantSettingsFn,
+};
+use crate::constraint::Constraint;
+use crate::filter::MatrixFilter;
+use crate::id::{IdFormat, IdTemplate};
+use crate::latency::LatencyRecorder;
+use crate::metrics::MetricsRecorder;
+use crate::plan::{list_mode_requested, MatrixPlan, PlanSource, SkipReason};
+use crate::report::SuiteReport;
+use crate::test_mode::{catch_panic, run_case, skip_outcome, TestReport};
+use crate::validate::{validate_plan, ValidationError};
 use crate::params::MatrixCellValue;
 
 use criterion::{
-  measurement::WallTime, AxisScale, Bencher, BenchmarkGroup, BenchmarkId, Criterion, PlotConfiguration,
+  measurement::WallTime, Bencher, BenchmarkGroup, Criterion,
   Throughput,
 };
+use std::cell::RefCell;
 use std::fmt::Debug;
 use std::time::Duration;
 
 pub type SyncSetupFn<S, Cfg, CtxT, SetupErr = String> = fn(&Cfg) -> Result<(CtxT, S), SetupErr>;
 pub type SyncBenchmarkLogicFn<S, Cfg, CtxT> = fn(CtxT, S, &Cfg) -> (CtxT, S, Duration);
 pub type SyncTeardownFn<S, Cfg, CtxT> = fn(CtxT, S, &Cfg) -> ();
+/// Logic function that also records per-operation latencies, set with `recording_logic`.
+pub type SyncRecordingLogicFn<S, Cfg, CtxT> = fn(CtxT, S, &Cfg, &LatencyRecorder) -> (CtxT, S, Duration);
 
 pub struct SyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr = String, SetupErr = String> {
   criterion: &'s mut Criterion<WallTime>,
@@ -26,8 +44,21 @@ pub struct SyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr = String, SetupErr = Stri
   benchmark_logic_fn: SyncBenchmarkLogicFn<S, Cfg, CtxT>,
   teardown_fn: SyncTeardownFn<S, Cfg, CtxT>,
   global_teardown_fn: Option<GlobalTeardownFn<Cfg>>,
-  criterion_group_configurator: Option<Box<dyn for<'g> Fn(&mut BenchmarkGroup<'g, WallTime>)>>,
-  throughput_calculator: Option<Box<dyn Fn(&Cfg) -> Throughput>>,
+  criterion_group_configurator: Option<CriterionGroupConfiguratorFn>,
+  throughput_calculator: Option<ThroughputFn<Cfg>>,
+  exclusions: Vec<Constraint>,
+  filters: Vec<MatrixFilter>,
+  config_validator: Option<ConfigValidatorFn<Cfg>>,
+  id_format: IdFormat,
+  group_by: Vec<String>,
+  x_axis: Option<String>,
+  variant_settings_fn: Option<VariantSettingsFn<Cfg>>,
+  secondary_throughputs: Vec<(String, ThroughputFn<Cfg>)>,
+  state_throughputs: Vec<(String, StateThroughputFn<S, Cfg, CtxT>)>,
+  metrics_fn: Option<MetricsFn<S, Cfg, CtxT>>,
+  concurrency_fn: Option<ConcurrencyFn<Cfg>>,
+  recording_logic_fn: Option<SyncRecordingLogicFn<S, Cfg, CtxT>>,
+  equivalence_check: Option<EquivalenceCheck<S, Cfg, CtxT>>,
 }
 
 impl<'s, S, Cfg, CtxT, ExtErr, SetupErr> SyncBenchmarkSuite<'s, S, Cfg, CtxT, ExtErr, SetupErr>
@@ -62,6 +93,19 @@ where
       global_teardown_fn: None,
       criterion_group_configurator: None,
       throughput_calculator: None,
+      exclusions: Vec::new(),
+      filters: Vec::new(),
+      config_validator: None,
+      id_format: IdFormat::default(),
+      group_by: Vec::new(),
+      x_axis: None,
+      variant_settings_fn: None,
+      secondary_throughputs: Vec::new(),
+      state_throughputs: Vec::new(),
+      metrics_fn: None,
+      concurrency_fn: None,
+      recording_logic_fn: None,
+      equivalence_check: None,
     }
   }
 
@@ -95,16 +139,332 @@ where
     self
   }
 
+  /// Sets the primary throughput, reported to Criterion and listed first (as `throughput`)
+  /// in the suite report returned by `run()`.
   pub fn throughput(mut self, f: impl Fn(&Cfg) -> Throughput + 'static) -> Self {
     self.throughput_calculator = Some(Box::new(f));
     self
   }
 
-  pub fn run(mut self) {
-    let abstract_combinations = generate_combinations(&self.parameter_axes);
+  /// Adds a further throughput dimension for the same measurement (e.g. messages/s next to
+  /// bytes/s). Criterion accepts only one throughput per benchmark, so secondary throughputs
+  /// appear only in the suite report returned by `run()`.
+  pub fn secondary_throughput(mut self, name: &str, f: impl Fn(&Cfg) -> Throughput + 'static) -> Self {
+    self.secondary_throughputs.push((name.to_string(), Box::new(f)));
+    self
+  }
+
+  /// Adds a throughput computed from the context and state at the end of each sample batch,
+  /// before teardown, e.g. the number of operations actually processed in that batch. The
+  /// amounts are summed over all batches and divided by the measured time. Like secondary
+  /// throughputs, these appear only in the suite report returned by `run()`.
+  pub fn throughput_from_state(mut self, name: &str, f: impl Fn(&CtxT, &S, &Cfg) -> Throughput + 'static) -> Self {
+    self.state_throughputs.push((name.to_string(), Box::new(f)));
+    self
+  }
+
+  /// Replaces the logic function given to `new` with one that also receives a
+  /// `LatencyRecorder`, for recording the latency of individual operations within an
+  /// iteration. The distribution (p50/p90/p99/p999/max) is reported per variant in the suite
+  /// report returned by `run()`, warm-up included.
+  pub fn recording_logic(mut self, f: SyncRecordingLogicFn<S, Cfg, CtxT>) -> Self {
+    self.recording_logic_fn = Some(f);
+    self
+  }
+
+  /// Checks that the implementations benchmarked along `axis` compute the same result. Before
+  /// timing starts, `run()` runs every variant once and takes `output` (the result, or a digest
+  /// of it) from the context and state after that iteration; variants differing only in `axis`
+  /// must produce equal outputs. Mismatches are reported as errors and the suite is then not
+  /// benchmarked. Requires parameter names.
+  pub fn check_equivalence<O: PartialEq + Debug + 'static>(
+    mut self,
+    axis: impl Into<String>,
+    output: impl Fn(&CtxT, &S, &Cfg) -> O + 'static,
+  ) -> Self {
+    self.equivalence_check = Some(EquivalenceCheck::new(axis.into(), output));
+    self
+  }
+
+  /// Records domain metrics after every measured iteration, e.g. a hit counter kept in the
+  /// context: `|ctx, _state, _cfg, metrics| metrics.record("hits", std::mem::take(&mut ctx.hits) as f64)`.
+  /// The hook runs outside the measured duration. Each metric is aggregated per variant (mean,
+  /// min, max, percentiles) in the suite report returned by `run()`.
+  pub fn collect_metrics(mut self, f: impl Fn(&mut CtxT, &S, &Cfg, &mut MetricsRecorder) + 'static) -> Self {
+    self.metrics_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Runs N copies of the benchmark logic at once, N being returned by `f` (usually read from
+  /// a matrix axis), to measure contention. Each copy gets its own context and state from the
+  /// setup function and runs on its own thread; the copies are released together by a barrier and
+  /// each sample batch is timed by the slowest copy's wall time. Throughputs are multiplied by
+  /// N, so they report the aggregate. `collect_metrics` runs once per copy at the end of each
+  /// sample batch rather than after every iteration.
+  pub fn concurrency(mut self, f: impl Fn(&Cfg) -> usize + 'static) -> Self {
+    self.concurrency_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Overrides Criterion settings per variant, e.g. fewer samples and a longer measurement
+  /// time for large payloads. The overrides are applied on top of the group's settings; the
+  /// variants after an overridden one continue in a fresh group of the same name, so Criterion's
+  /// HTML summary for that group covers only the last stretch. Settings Criterion would reject
+  /// (such as a sample size below 10) mark the variant as an invalid configuration.
+  pub fn variant_settings(mut self, f: impl Fn(&Cfg) -> VariantSettings + 'static) -> Self {
+    self.variant_settings_fn = Some(Box::new(f));
+    self
+  }
+
+  /// Excludes every combination matching `constraint`. Excluded combinations are dropped
+  /// before extraction and global setup. Constraints refer to axes by name, so parameter
+  /// names must be set for exclusions to apply.
+  pub fn exclude(mut self, constraint: Constraint) -> Self {
+    self.exclusions.push(constraint);
+    self
+  }
+
+  /// Adds several exclusion constraints at once, e.g. the `exclusions` of a loaded matrix file.
+  pub fn exclusions(mut self, constraints: impl IntoIterator<Item = Constraint>) -> Self {
+    self.exclusions.extend(constraints);
+    self
+  }
+
+  /// Narrows (or overrides) axis values before any combination is generated, so filtered-out
+  /// variants never reach the extractor or global setup. The `BENCH_MATRIX_FILTER` environment
+  /// variable is applied on top of any filters set here.
+  pub fn filter(mut self, filter: MatrixFilter) -> Self {
+    self.filters.push(filter);
+    self
+  }
+
+  /// Sets how combinations are turned into benchmark IDs. The default, `IdFormat::Readable`,
+  /// can map distinct combinations to the same ID; such duplicates are refused rather than
+  /// handed to Criterion. `IdFormat::Reversible` never collides.
+  pub fn id_format(mut self, format: IdFormat) -> Self {
+    self.id_format = format;
+    self
+  }
+
+  /// Lays out benchmark IDs with `template`, e.g. `"{Backend}/{Size:bytes}/x{Threads}"`.
+  /// Shorthand for `.id_format(IdFormat::Template(template))`; requires parameter names.
+  pub fn id_template(self, template: IdTemplate) -> Self {
+    self.id_format(IdFormat::Template(template))
+  }
+
+  /// Splits the suite into one Criterion group per value of the given axes, named e.g.
+  /// `Suite/Backend-Uring`, so summary plots only compare related variants.
+  /// Requires parameter names.
+  pub fn group_by(mut self, axes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+    self.group_by = axes.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Uses the values of `axis` as the Criterion benchmark parameter, so Criterion's line
+  /// charts plot each group against it. The axis should be numeric. Requires parameter names.
+  pub fn x_axis(mut self, axis: impl Into<String>) -> Self {
+    self.x_axis = Some(axis.into());
+    self
+  }
+
+  /// Resolves the suite's matrix without running anything: applies filters and exclusion
+  /// constraints, runs the extractor over every combination and computes the benchmark IDs.
+  /// No setup function is called and nothing is registered with Criterion.
+  pub fn plan(&self) -> Result<MatrixPlan<Cfg>, String> {
+    if let Some(ref equivalence_check) = self.equivalence_check {
+      equivalence_check.axis_index(self.parameter_names.as_deref())?;
+    }
+    let check = |cfg: &Cfg| -> Result<(), String> {
+      if let Some(ref validator) = self.config_validator {
+        validator(cfg)?;
+      }
+      if let Some(ref settings_fn) = self.variant_settings_fn {
+        settings_fn(cfg).check().map_err(|e| format!("invalid variant settings: {}", e))?;
+      }
+      if let Some(ref concurrency_fn) = self.concurrency_fn
+        && concurrency_fn(cfg) == 0
+      {
+        return Err("concurrency must be at least 1".to_string());
+      }
+      Ok(())
+    };
+    PlanSource {
+      suite_name: &self.suite_base_name,
+      parameter_names: self.parameter_names.as_deref(),
+      parameter_axes: &self.parameter_axes,
+      filters: &self.filters,
+      exclusions: &self.exclusions,
+      extractor: &*self.extractor_fn,
+      config_validator: Some(&check),
+      id_format: &self.id_format,
+      group_by: &self.group_by,
+      x_axis: self.x_axis.as_deref(),
+    }
+    .plan()
+  }
+
+  /// Sets a check applied to every extracted configuration. Configurations it rejects are
+  /// reported by `validate()` and skipped by `run()`.
+  pub fn validate_config(mut self, f: impl Fn(&Cfg) -> Result<(), String> + 'static) -> Self {
+    self.config_validator = Some(Box::new(f));
+    self
+  }
+
+  /// Checks the whole matrix before anything runs and returns every problem at once:
+  /// parameter name/axis count disagreement, filter and constraint errors, extractor
+  /// failures, configurations rejected by `validate_config`, and duplicate benchmark IDs.
+  /// No setup function is called and nothing is registered with Criterion.
+  pub fn validate(&self) -> Result<(), ValidationError> {
+    validate_plan(
+      &self.suite_base_name,
+      self.parameter_names.as_deref(),
+      self.parameter_axes.len(),
+      self.plan(),
+    )
+  }
+
+  /// Prints the final benchmark IDs as a table, marking which combinations would be skipped
+  /// and why, and returns the plan. No setup function is called and nothing is registered
+  /// with Criterion. `run()` does this instead of benchmarking when `BENCH_MATRIX_LIST` is set.
+  pub fn list(&self) -> Result<MatrixPlan<Cfg>, String> {
+    let plan = self.plan().inspect_err(|e| {
+      eprintln!("[BenchMatrix::Sync] [ERROR] Suite '{}': {}.", self.suite_base_name, e);
+    })?;
+    for warning in &plan.warnings {
+      eprintln!("[BenchMatrix::Sync] [WARN] Suite '{}': {}", self.suite_base_name, warning);
+    }
+    println!("{}", plan.render_table());
+    Ok(plan)
+  }
+
+  /// Runs every combination once as a test instead of benchmarking it: global setup, setup,
+  /// one iteration of the logic (with `collect_metrics` and `throughput_from_state`), teardown
+  /// and global teardown, without Criterion. Each combination is reported in libtest's format;
+  /// extractor, validator and setup errors and panics are failures. Call `assert_passed()` on
+  /// the result from a `#[test]` function, with `Criterion::default()` as the Criterion.
+  pub fn run_tests(mut self) -> TestReport {
+    let mut report = TestReport::new(&self.suite_base_name);
+    let plan = match self.plan() {
+      Ok(plan) => plan,
+      Err(e) => {
+        report.error = Some(e);
+        println!("{}", report.render_summary());
+        return report;
+      }
+    };
+
+    println!("\nrunning {} tests", plan.variants.len());
+    for variant in plan.variants {
+      let outcome = match variant.config {
+        Ok(ref cfg) => run_case(|| self.run_once(cfg).map(|_| ())),
+        Err(ref reason) => skip_outcome(reason),
+      };
+      report.record(variant.full_id(), outcome);
+    }
+    println!("\n{}", report.render_summary());
+    report
+  }
+
+  /// Runs every runnable variant once, as `run_tests()` does, and checks that variants
+  /// differing only in the implementation axis set with `check_equivalence` produced equal
+  /// outputs. Variants that fail or panic are reported as unchecked. Succeeds without running
+  /// anything if `check_equivalence` is not set. `run()` calls this before timing starts and
+  /// skips the suite if it fails.
+  pub fn verify_equivalence(&mut self) -> Result<(), EquivalenceError> {
+    if self.equivalence_check.is_none() {
+      return Ok(());
+    }
+    let plan = self
+      .plan()
+      .map_err(|reason| EquivalenceError::suite_failure(&self.suite_base_name, reason))?;
+    self.verify_equivalence_of(&plan)
+  }
+
+  /// `verify_equivalence` for an already built plan, so `run()` plans the suite only once.
+  fn verify_equivalence_of(&mut self, plan: &MatrixPlan<Cfg>) -> Result<(), EquivalenceError> {
+    let Some(ref equivalence_check) = self.equivalence_check else {
+      return Ok(());
+    };
+    let axis_index = equivalence_check
+      .axis_index(self.parameter_names.as_deref())
+      .map_err(|reason| EquivalenceError::suite_failure(&self.suite_base_name, reason))?;
+    let names = self.parameter_names.clone().unwrap_or_default();
+
+    let mut collector = OutputCollector::new(axis_index, &names);
+    for variant in &plan.variants {
+      if let Ok(ref cfg) = variant.config {
+        let output = catch_panic(|| self.run_once(cfg)).map(|output| output.expect("check_equivalence is set"));
+        collector.record(variant.full_id(), &variant.combination, output);
+      }
+    }
+    collector.finish(&self.suite_base_name)
+  }
+
+  /// Runs global setup, setup, one iteration of the logic, teardown and global teardown,
+  /// returning the output taken for `check_equivalence`, if set.
+  fn run_once(&mut self, cfg: &Cfg) -> Result<Option<Box<dyn Output>>, String> {
+    if let Some(ref mut global_setup) = self.global_setup_fn {
+      global_setup(cfg).map_err(|e| format!("global setup failed: {}", e))?;
+    }
+    let result = match (self.setup_fn)(cfg) {
+      Ok((user_ctx, setup_data_instance)) => {
+        let (mut user_ctx, setup_data_instance, _) = match self.recording_logic_fn {
+          Some(recording_logic) => recording_logic(user_ctx, setup_data_instance, cfg, &LatencyRecorder::new()),
+          None => (self.benchmark_logic_fn)(user_ctx, setup_data_instance, cfg),
+        };
+        let output = self
+          .equivalence_check
+          .as_ref()
+          .map(|equivalence_check| equivalence_check.output(&user_ctx, &setup_data_instance, cfg));
+        if let Some(ref collect) = self.metrics_fn {
+          collect(&mut user_ctx, &setup_data_instance, cfg, &mut MetricsRecorder::default());
+        }
+        for (_, state_throughput) in &self.state_throughputs {
+          state_throughput(&user_ctx, &setup_data_instance, cfg);
+        }
+        (self.teardown_fn)(user_ctx, setup_data_instance, cfg);
+        Ok(output)
+      }
+      Err(e) => Err(format!("setup failed: {:?}", e)),
+    };
+    let global_teardown = match self.global_teardown_fn {
+      Some(ref mut global_teardown) => global_teardown(cfg).map_err(|e| format!("global teardown failed: {}", e)),
+      None => Ok(()),
+    };
+    result.and_then(|output| global_teardown.map(|()| output))
+  }
+
+  /// Runs every variant through Criterion and returns bench_matrix's own report of them.
+  /// In list mode, or if the suite cannot be planned, nothing runs and the report is empty.
+  pub fn run(mut self) -> SuiteReport {
+    let mut report = SuiteReport {
+      suite_name: self.suite_base_name.clone(),
+      parameter_names: self.parameter_names.clone(),
+      variants: Vec::new(),
+    };
+
+    if list_mode_requested() {
+      let _ = self.list();
+      return report;
+    }
+
+    let plan = match self.plan() {
+      Ok(plan) => plan,
+      Err(e) => {
+        eprintln!(
+          "[BenchMatrix::Sync] [ERROR] Suite '{}': {}. Skipping this suite.",
+          self.suite_base_name, e
+        );
+        return report;
+      }
+    };
+
+    for warning in &plan.warnings {
+      eprintln!("[BenchMatrix::Sync] [WARN] Suite '{}': {}", self.suite_base_name, warning);
+    }
 
-    if abstract_combinations.len() == 0 {
-      let reason = if self.parameter_axes.is_empty() {
+    if plan.variants.is_empty() {
+      let reason = if plan.parameter_axes.is_empty() {
         "no parameter axes defined"
       } else {
         "no combinations generated (e.g., an axis was empty)"
@@ -113,109 +473,232 @@ where
         "[BenchMatrix::Sync] Suite '{}': {}. Nothing to run.",
         self.suite_base_name, reason
       );
-      return;
+      return report;
     }
 
-    let total_variants = abstract_combinations.len();
+    // Timings of implementations that disagree would compare wrong code, so none are taken.
+    if let Err(e) = self.verify_equivalence_of(&plan) {
+      eprintln!("[BenchMatrix::Sync] [ERROR] {}", e);
+      eprintln!(
+        "[BenchMatrix::Sync] [ERROR] Suite '{}': equivalence check failed. Skipping this suite.",
+        self.suite_base_name
+      );
+      return report;
+    }
+
+    let total_variants = plan.variants.len();
     let mut variants_run_count = 0;
     let mut variants_skipped_extraction = 0;
     let mut variants_skipped_global_setup = 0;
+    let mut variants_excluded = 0;
+    let mut variants_skipped_invalid = 0;
+    let mut variants_skipped_duplicate = 0;
 
-    let mut group = self.criterion.benchmark_group(&self.suite_base_name);
-    
-    if let Some(ref configurator) = self.criterion_group_configurator {
-      configurator(&mut group);
-    } else {
-      group
-        .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic))
-        .sample_size(10);
-    }
+    let mut variants = plan.variants.into_iter().peekable();
+    while let Some(group_name) = variants.peek().map(|variant| variant.group_name.clone()) {
+      let mut group = self.criterion.benchmark_group(&group_name);
 
-    for abstract_combo in abstract_combinations {
-      let concrete_config = match (self.extractor_fn)(&abstract_combo) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-          eprintln!(
-                        "[BenchMatrix::Sync] [ERROR] Suite '{}', Combination ID '{}': Failed to extract concrete configuration: {:?}. Skipping this combination.",
-                        self.suite_base_name, abstract_combo.id_suffix(), e
-                    );
-          variants_skipped_extraction += 1;
-          continue;
-        }
-      };
+      apply_group_settings(&mut group, self.criterion_group_configurator.as_ref());
+      let mut group_overridden = false;
+
+      while let Some(variant) = variants.next_if(|variant| variant.group_name == group_name) {
+        let full_id = variant.full_id();
+        let abstract_combo = variant.combination;
+        let concrete_config = match variant.config {
+          Ok(cfg) => cfg,
+          Err(SkipReason::Excluded) => {
+            variants_excluded += 1;
+            continue;
+          }
+          Err(SkipReason::Extraction(e)) => {
+            eprintln!(
+              "[BenchMatrix::Sync] [ERROR] Suite '{}', Combination ID '{}': Failed to extract concrete configuration: {}. Skipping this combination.",
+              self.suite_base_name,
+              abstract_combo.id_suffix(),
+              e
+            );
+            variants_skipped_extraction += 1;
+            continue;
+          }
+          Err(SkipReason::InvalidConfig(e)) => {
+            eprintln!(
+              "[BenchMatrix::Sync] [ERROR] Suite '{}', Combination ID '{}': Configuration rejected by validator: {}. Skipping this combination.",
+              self.suite_base_name,
+              abstract_combo.id_suffix(),
+              e
+            );
+            variants_skipped_invalid += 1;
+            continue;
+          }
+          Err(SkipReason::DuplicateId(first)) => {
+            eprintln!(
+              "[BenchMatrix::Sync] [ERROR] Suite '{}', Benchmark ID '{}': ID already used by {}. Skipping this combination; consider IdFormat::Reversible.",
+              self.suite_base_name, full_id, first
+            );
+            variants_skipped_duplicate += 1;
+            continue;
+          }
+        };
 
-      if let Some(ref mut global_setup) = self.global_setup_fn {
-        if let Err(e) = global_setup(&concrete_config) {
+        if let Some(ref mut global_setup) = self.global_setup_fn
+          && let Err(e) = global_setup(&concrete_config)
+        {
           eprintln!(
-                        "[BenchMatrix::Sync] [ERROR] Suite '{}', Config (ID '{}', Detail {:?}): Global setup failed: {}. Skipping benchmarks for this configuration.",
-                        self.suite_base_name, abstract_combo.id_suffix(), concrete_config, e
-                    );
+            "[BenchMatrix::Sync] [ERROR] Suite '{}', Config (ID '{}', Detail {:?}): Global setup failed: {}. Skipping benchmarks for this configuration.",
+            self.suite_base_name,
+            abstract_combo.id_suffix(),
+            concrete_config,
+            e
+          );
           variants_skipped_global_setup += 1;
-          if let Some(ref mut global_teardown_on_setup_fail) = self.global_teardown_fn {
-            if let Err(td_err) = global_teardown_on_setup_fail(&concrete_config) {
-              eprintln!(
-                                "[BenchMatrix::Sync] [WARN] Suite '{}', Config (ID '{}'): Global teardown after global setup failure also failed: {}",
-                                self.suite_base_name, abstract_combo.id_suffix(), td_err
-                            );
-            }
+          if let Some(ref mut global_teardown_on_setup_fail) = self.global_teardown_fn
+            && let Err(td_err) = global_teardown_on_setup_fail(&concrete_config)
+          {
+            eprintln!(
+              "[BenchMatrix::Sync] [WARN] Suite '{}', Config (ID '{}'): Global teardown after global setup failure also failed: {}",
+              self.suite_base_name,
+              abstract_combo.id_suffix(),
+              td_err
+            );
           }
           continue;
         }
-      }
 
-      let parameter_string = if let Some(names) = &self.parameter_names {
-        abstract_combo.id_suffix_with_names(names).strip_prefix('_').unwrap_or("").to_string()
-      } else {
-        abstract_combo.id_suffix().strip_prefix('_').unwrap_or("").to_string()
-      };
+        if let Some(ref settings_fn) = self.variant_settings_fn {
+          // Criterion cannot unset a group setting, so the variants after an overridden one continue
+          // in a fresh group of the same name, which starts from the Criterion instance's settings.
+          if group_overridden {
+            group.finish();
+            group = self.criterion.benchmark_group(&group_name);
+            apply_group_settings(&mut group, self.criterion_group_configurator.as_ref());
+          }
+          group_overridden = settings_fn(&concrete_config).apply(&mut group);
+        }
+
+        let participant_count = self.concurrency_fn.as_ref().map(|concurrency_fn| concurrency_fn(&concrete_config));
+        let aggregate = |throughput: Throughput| match participant_count {
+          Some(participants) => scale_throughput(&throughput, participants),
+          None => throughput,
+        };
 
-      let bench_id = BenchmarkId::from_parameter(&parameter_string);
-
-      let setup_fn_ptr = self.setup_fn;
-      let benchmark_logic_fn_ptr = self.benchmark_logic_fn;
-      let teardown_fn_ptr = self.teardown_fn;
-
-      // Use `bench_with_input` to create a configurable benchmark.
-      let bench_registration = group.bench_with_input(bench_id, &concrete_config, 
-        move |b: &mut Bencher<'_, WallTime>, cfg: &Cfg| {
-          b.iter_custom(|iters_count_hint| {
-            // The `cfg` from the closure is the specific config for this benchmark run.
-            let cfg_clone_per_sample_batch = cfg.clone();
-            
-            let (mut user_ctx, mut setup_data_instance) =
-              (setup_fn_ptr)(&cfg_clone_per_sample_batch).unwrap_or_else(|e| {
-                panic!(
-                  "[BenchMatrix::Sync] PANIC in sample: Sync setup_fn failed for config {:?}: {:?}",
-                  cfg_clone_per_sample_batch, e
-                )
-              });
+        // Criterion applies the group's throughput to the benchmarks registered after it is set.
+        let mut declared_throughputs: Vec<(String, Throughput)> = Vec::new();
+        if let Some(ref throughput_calc) = self.throughput_calculator {
+          let throughput = aggregate(throughput_calc(&concrete_config));
+          group.throughput(throughput.clone());
+          declared_throughputs.push(("throughput".to_string(), throughput));
+        }
+        for (name, throughput_calc) in &self.secondary_throughputs {
+          declared_throughputs.push((name.clone(), aggregate(throughput_calc(&concrete_config))));
+        }
+        let variant_totals = RefCell::new(VariantTotals::default());
+        let totals = &variant_totals;
+        let state_throughput_fns = &self.state_throughputs;
+        let metrics_fn = self.metrics_fn.as_deref();
+        let variant_latency = LatencyRecorder::new();
+        let latency = &variant_latency;
+
+        let bench_id = criterion_benchmark_id(&variant.bench_id, variant.parameter.as_deref());
+
+        let setup_fn_ptr = self.setup_fn;
+        let benchmark_logic_fn_ptr = self.benchmark_logic_fn;
+        let teardown_fn_ptr = self.teardown_fn;
+        let recording_logic_fn_ptr = self.recording_logic_fn;
+
+        // Use `bench_with_input` to create a configurable benchmark.
+        group.bench_with_input(bench_id, &concrete_config, 
+          move |b: &mut Bencher<'_, WallTime>, cfg: &Cfg| {
+            b.iter_custom(|iters_count_hint| {
+              // The `cfg` from the closure is the specific config for this benchmark run.
+              let cfg_clone_per_sample_batch = cfg.clone();
+
+              if let Some(participants) = participant_count {
+                let participant_states = (0..participants)
+                  .map(|_| {
+                    (setup_fn_ptr)(&cfg_clone_per_sample_batch).unwrap_or_else(|e| {
+                      panic!(
+                        "[BenchMatrix::Sync] PANIC in sample: Sync setup_fn failed for config {:?}: {:?}",
+                        cfg_clone_per_sample_batch, e
+                      )
+                    })
+                  })
+                  .collect();
+                // One recorder per participant, merged afterwards, so recording never makes the
+                // participants wait on each other.
+                let participant_latencies: Vec<LatencyRecorder> = (0..participants).map(|_| LatencyRecorder::new()).collect();
+                let (participant_states, slowest_duration) = concurrency::run_threads(
+                  participant_states,
+                  iters_count_hint,
+                  &cfg_clone_per_sample_batch,
+                  |participant, user_ctx, setup_data_instance, cfg: &Cfg| match recording_logic_fn_ptr {
+                    Some(recording_logic) => {
+                      recording_logic(user_ctx, setup_data_instance, cfg, &participant_latencies[participant])
+                    }
+                    None => (benchmark_logic_fn_ptr)(user_ctx, setup_data_instance, cfg),
+                  },
+                );
+                for participant_latency in &participant_latencies {
+                  latency.merge(participant_latency);
+                }
+
+                for (mut user_ctx, setup_data_instance) in participant_states {
+                  if let Some(collect) = metrics_fn {
+                    collect(&mut user_ctx, &setup_data_instance, &cfg_clone_per_sample_batch, &mut totals.borrow_mut().metrics);
+                  }
+                  for (index, (_, state_throughput)) in state_throughput_fns.iter().enumerate() {
+                    let throughput = state_throughput(&user_ctx, &setup_data_instance, &cfg_clone_per_sample_batch);
+                    totals.borrow_mut().add_state_throughput(index, &throughput);
+                  }
+                  (teardown_fn_ptr)(user_ctx, setup_data_instance, &cfg_clone_per_sample_batch);
+                }
+
+                totals.borrow_mut().record_run_batch(iters_count_hint, slowest_duration);
+                return slowest_duration;
+              }
+
+              let (mut user_ctx, mut setup_data_instance) =
+                (setup_fn_ptr)(&cfg_clone_per_sample_batch).unwrap_or_else(|e| {
+                  panic!(
+                    "[BenchMatrix::Sync] PANIC in sample: Sync setup_fn failed for config {:?}: {:?}",
+                    cfg_clone_per_sample_batch, e
+                  )
+                });
               
-            let mut total_duration_for_sample_batch = Duration::new(0, 0);
-            for _i in 0..iters_count_hint {
-              let (ctx_after_iter, s_after_iter, measured_duration) =
-                (benchmark_logic_fn_ptr)(user_ctx, setup_data_instance, &cfg_clone_per_sample_batch);
+              let mut total_duration_for_sample_batch = Duration::new(0, 0);
+              for _i in 0..iters_count_hint {
+                let (ctx_after_iter, s_after_iter, measured_duration) = match recording_logic_fn_ptr {
+                  Some(recording_logic) => recording_logic(user_ctx, setup_data_instance, &cfg_clone_per_sample_batch, latency),
+                  None => (benchmark_logic_fn_ptr)(user_ctx, setup_data_instance, &cfg_clone_per_sample_batch),
+                };
 
-              total_duration_for_sample_batch += measured_duration;
-              user_ctx = ctx_after_iter;
-              setup_data_instance = s_after_iter;
-            }
+                total_duration_for_sample_batch += measured_duration;
+                user_ctx = ctx_after_iter;
+                setup_data_instance = s_after_iter;
 
-            (teardown_fn_ptr)(user_ctx, setup_data_instance, &cfg_clone_per_sample_batch);
+                if let Some(collect) = metrics_fn {
+                  collect(&mut user_ctx, &setup_data_instance, &cfg_clone_per_sample_batch, &mut totals.borrow_mut().metrics);
+                }
+              }
 
-            total_duration_for_sample_batch
-          });
-        }
-      );
+              for (index, (_, state_throughput)) in state_throughput_fns.iter().enumerate() {
+                let throughput = state_throughput(&user_ctx, &setup_data_instance, &cfg_clone_per_sample_batch);
+                totals.borrow_mut().add_state_throughput(index, &throughput);
+              }
 
-      // Now, configure the throughput on the returned benchmark object.
-      if let Some(ref throughput_calc) = self.throughput_calculator {
-        bench_registration.throughput(throughput_calc(&concrete_config));
-      }
+              (teardown_fn_ptr)(user_ctx, setup_data_instance, &cfg_clone_per_sample_batch);
 
-      variants_run_count += 1;
+              totals.borrow_mut().record_run_batch(iters_count_hint, total_duration_for_sample_batch);
+              total_duration_for_sample_batch
+            });
+          }
+        );
+
+
+        variants_run_count += 1;
 
-      if let Some(ref mut global_teardown) = self.global_teardown_fn {
-        if let Err(e) = global_teardown(&concrete_config) {
+        if let Some(ref mut global_teardown) = self.global_teardown_fn
+          && let Err(e) = global_teardown(&concrete_config)
+        {
           eprintln!(
             "[BenchMatrix::Sync] [WARN] Suite '{}', Config (ID '{}', Detail {:?}): Global teardown failed: {}",
             self.suite_base_name,
@@ -224,18 +707,36 @@ where
             e
           );
         }
+
+        // Variants deselected by Criterion's own command-line filter are never sampled.
+        let mut totals = variant_totals.into_inner();
+        totals.latency = variant_latency.summarize();
+        if totals.iterations > 0 {
+          let state_names: Vec<String> = self.state_throughputs.iter().map(|(name, _)| name.clone()).collect();
+          report
+            .variants
+            .push(totals.into_report(full_id, abstract_combo, &declared_throughputs, &state_names));
+        }
       }
+
+      group.finish();
     }
-    
-    group.finish();
 
-    if variants_skipped_extraction > 0 || variants_skipped_global_setup > 0 {
+    if variants_skipped_extraction > 0
+      || variants_skipped_invalid > 0
+      || variants_skipped_duplicate > 0
+      || variants_skipped_global_setup > 0
+      || variants_excluded > 0
+    {
       eprintln!(
-                "[BenchMatrix::Sync] Suite '{}' summary: {} variants attempted, {} successfully run, {} skipped (extraction), {} skipped (global setup).",
+                "[BenchMatrix::Sync] Suite '{}' summary: {} variants attempted, {} successfully run, {} excluded (constraints), {} skipped (extraction), {} skipped (invalid config), {} skipped (duplicate ID), {} skipped (global setup).",
                 self.suite_base_name,
                 total_variants,
                 variants_run_count,
+                variants_excluded,
                 variants_skipped_extraction,
+                variants_skipped_invalid,
+                variants_skipped_duplicate,
                 variants_skipped_global_setup
             );
     } else if variants_run_count > 0 {
@@ -244,5 +745,131 @@ where
         self.suite_base_name, variants_run_count
       );
     }
+
+    if !self.secondary_throughputs.is_empty()
+      || !self.state_throughputs.is_empty()
+      || self.metrics_fn.is_some()
+      || self.recording_logic_fn.is_some()
+    {
+      println!("{}", report.render());
+    }
+    report
   }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::params::AbstractCombination;
+  use crate::test_mode::TestOutcome;
+
+  fn setup(divisor: &u64) -> Result<((), u64), String> {
+    if *divisor == 3 { Err("refused".to_string()) } else { Ok(((), 0)) }
+  }
+
+  fn logic(ctx: (), _state: u64, divisor: &u64) -> ((), u64, Duration) {
+    (ctx, 100 / *divisor, Duration::ZERO)
+  }
+
+  #[test]
+  fn test_run_tests_reports_each_combination() {
+    let mut criterion = Criterion::default();
+    let axis = [0, 1, 2, 3, 4].map(MatrixCellValue::Unsigned).to_vec();
+    let report = SyncBenchmarkSuite::new(
+      &mut criterion,
+      "Divide".to_string(),
+      Some(vec!["Divisor".to_string()]),
+      vec![axis],
+      Box::new(|combo: &AbstractCombination| match combo.get_u64(0)? {
+        4 => Err("no fours".to_string()),
+        divisor => Ok(divisor),
+      }),
+      setup,
+      logic,
+      |_ctx, state, _cfg| assert!(state > 0),
+    )
+    .exclude(Constraint::new().when("Divisor", [2u64]))
+    .run_tests();
+
+    let outcomes: Vec<(&str, &TestOutcome)> = report.tests.iter().map(|test| (test.id.as_str(), &test.outcome)).collect();
+    assert!(matches!(outcomes[0], ("Divide/Divisor-0", TestOutcome::Failed(message)) if message.contains("panicked")));
+    assert_eq!(outcomes[1], ("Divide/Divisor-1", &TestOutcome::Passed));
+    assert_eq!(outcomes[2], ("Divide/Divisor-2", &TestOutcome::Ignored));
+    assert_eq!(outcomes[3], ("Divide/Divisor-3", &TestOutcome::Failed("setup failed: \"refused\"".to_string())));
+    assert!(matches!(outcomes[4], ("Divide/Divisor-4", TestOutcome::Failed(message)) if message.contains("no fours")));
+  }
+
+  #[test]
+  fn test_verify_equivalence_groups_by_the_other_axes() {
+    fn setup(_cfg: &(String, u64)) -> Result<((), Vec<u64>), String> {
+      Ok(((), Vec::new()))
+    }
+    fn sort(ctx: (), _state: Vec<u64>, (algorithm, size): &(String, u64)) -> ((), Vec<u64>, Duration) {
+      let mut values: Vec<u64> = (0..*size).rev().collect();
+      if algorithm != "Broken" || *size < 3 {
+        values.sort();
+      }
+      (ctx, values, Duration::ZERO)
+    }
+
+    let mut criterion = Criterion::default();
+    let algorithms = ["Quick", "Merge", "Broken"].map(MatrixCellValue::from).to_vec();
+    let sizes = [2, 3].map(MatrixCellValue::Unsigned).to_vec();
+    let mut suite = SyncBenchmarkSuite::new(
+      &mut criterion,
+      "Sort".to_string(),
+      Some(vec!["Algorithm".to_string(), "Size".to_string()]),
+      vec![algorithms, sizes],
+      Box::new(|combo: &AbstractCombination| Ok::<_, String>((combo.get_tag(0)?.to_string(), combo.get_u64(1)?))),
+      setup,
+      sort,
+      |_ctx, _state, _cfg| {},
+    )
+    .check_equivalence("Algorithm", |_ctx, sorted: &Vec<u64>, _cfg| sorted.clone());
+
+    let error = suite.verify_equivalence().unwrap_err();
+    assert_eq!(error.mismatches.len(), 1, "{}", error);
+    assert_eq!(error.mismatches[0].group, "Size=3");
+    assert_eq!(error.mismatches[0].outputs[2], ("Sort/Algorithm-Broken_Size-3".to_string(), "[2, 1, 0]".to_string()));
+    assert!(error.failures.is_empty());
+    // The mismatch keeps run() from timing any variant.
+    assert!(suite.run().variants.is_empty());
+  }
+
+  #[test]
+  fn test_variant_settings_do_not_leak_into_the_criterion_settings() {
+    fn sample_count(root: &std::path::Path, id: &str) -> usize {
+      let sample = std::fs::read_to_string(root.join("Settings").join(id).join("new").join("sample.json")).unwrap();
+      let iters = &sample[sample.find("\"iters\":[").unwrap()..];
+      iters[..iters.find(']').unwrap()].split(',').count()
+    }
+
+    let root = std::env::temp_dir().join(format!("bench_matrix_variant_settings_{}", std::process::id()));
+    let mut criterion = Criterion::default()
+      .sample_size(12)
+      .warm_up_time(Duration::from_millis(1))
+      .measurement_time(Duration::from_millis(10))
+      .output_directory(&root)
+      .without_plots();
+    let axis = [1, 2, 3].map(MatrixCellValue::Unsigned).to_vec();
+    SyncBenchmarkSuite::new(
+      &mut criterion,
+      "Settings".to_string(),
+      Some(vec!["Size".to_string()]),
+      vec![axis],
+      Box::new(|combo: &AbstractCombination| combo.get_u64(0)),
+      |_size: &u64| Ok::<_, String>(((), 0u64)),
+      |ctx, state, _size: &u64| (ctx, state, Duration::from_nanos(1)),
+      |_ctx, _state, _size| {},
+    )
+    .configure_criterion_group(|_group| {})
+    .variant_settings(|size: &u64| match size {
+      2 => VariantSettings::new().sample_size(15),
+      _ => VariantSettings::new(),
+    })
+    .run();
+
+    let counts: Vec<usize> = ["Size-1", "Size-2", "Size-3"].iter().map(|id| sample_count(&root, id)).collect();
+    std::fs::remove_dir_all(&root).unwrap();
+    assert_eq!(counts, [12, 15, 12]);
+  }
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "criterion_integration")]
+
+use super::CriterionGroupConfiguratorFn;
+
+use criterion::{measurement::WallTime, AxisScale, BenchmarkGroup, PlotConfiguration, SamplingMode};
+use std::time::Duration;
+
+/// Criterion measurement settings for a single variant, returned by the hook passed to the
+/// suites' `variant_settings`. Fields left as `None` keep the group's settings (those applied
+/// by `configure_criterion_group`, or the suite defaults).
+///
+/// # Example
+/// ```
+/// # use bench_matrix::VariantSettings;
+/// # use std::time::Duration;
+/// let large_payload = VariantSettings::new()
+///   .sample_size(10)
+///   .measurement_time(Duration::from_secs(20));
+/// assert!(large_payload.check().is_ok());
+/// assert!(VariantSettings::new().sample_size(5).check().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VariantSettings {
+  pub sample_size: Option<usize>,
+  pub measurement_time: Option<Duration>,
+  pub warm_up_time: Option<Duration>,
+  pub noise_threshold: Option<f64>,
+  pub sampling_mode: Option<SamplingMode>,
+}
+
+impl VariantSettings {
+  /// Creates settings that override nothing.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn sample_size(mut self, n: usize) -> Self {
+    self.sample_size = Some(n);
+    self
+  }
+
+  pub fn measurement_time(mut self, duration: Duration) -> Self {
+    self.measurement_time = Some(duration);
+    self
+  }
+
+  pub fn warm_up_time(mut self, duration: Duration) -> Self {
+    self.warm_up_time = Some(duration);
+    self
+  }
+
+  pub fn noise_threshold(mut self, threshold: f64) -> Self {
+    self.noise_threshold = Some(threshold);
+    self
+  }
+
+  pub fn sampling_mode(mut self, mode: SamplingMode) -> Self {
+    self.sampling_mode = Some(mode);
+    self
+  }
+
+  /// Checks the values Criterion would otherwise reject with a panic: a sample size below 10,
+  /// zero durations and a negative noise threshold.
+  pub fn check(&self) -> Result<(), String> {
+    if let Some(n) = self.sample_size
+      && n < 10
+    {
+      return Err(format!("sample size must be at least 10, got {}", n));
+    }
+    if self.measurement_time.is_some_and(|duration| duration.is_zero()) {
+      return Err("measurement time must be greater than zero".to_string());
+    }
+    if self.warm_up_time.is_some_and(|duration| duration.is_zero()) {
+      return Err("warm-up time must be greater than zero".to_string());
+    }
+    if let Some(threshold) = self.noise_threshold
+      && (threshold.is_nan() || threshold < 0.0)
+    {
+      return Err(format!("noise threshold must be non-negative, got {}", threshold));
+    }
+    Ok(())
+  }
+
+  /// Applies the overridden settings to `group` and reports whether there were any.
+  pub(crate) fn apply(&self, group: &mut BenchmarkGroup<'_, WallTime>) -> bool {
+    if let Some(n) = self.sample_size {
+      group.sample_size(n);
+    }
+    if let Some(duration) = self.measurement_time {
+      group.measurement_time(duration);
+    }
+    if let Some(duration) = self.warm_up_time {
+      group.warm_up_time(duration);
+    }
+    if let Some(threshold) = self.noise_threshold {
+      group.noise_threshold(threshold);
+    }
+    if let Some(mode) = self.sampling_mode {
+      group.sampling_mode(mode);
+    }
+    self.sample_size.is_some()
+      || self.measurement_time.is_some()
+      || self.warm_up_time.is_some()
+      || self.noise_threshold.is_some()
+      || self.sampling_mode.is_some()
+  }
+}
+
+/// Applies the suite-wide settings to a freshly created group: the user's configurator if set,
+/// otherwise the suite defaults.
+pub(crate) fn apply_group_settings(group: &mut BenchmarkGroup<'_, WallTime>, configurator: Option<&CriterionGroupConfiguratorFn>) {
+  match configurator {
+    Some(configurator) => configurator(group),
+    None => {
+      group
+        .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic))
+        .sample_size(10);
+    }
+  }
+}
@@ -0,0 +1,516 @@
+#![cfg(feature = "json")]
+
+//! Exporting a suite's results as a table keyed by axis values, in CSV or JSON.
+//!
+//! Criterion stores each benchmark's statistics under
+//! `target/criterion/<group>/<id>/new/estimates.json`. [`ResultsTable`] finds the estimates of
+//! every variant of a [`SuiteReport`] and lays them out with one row per combination: a column
+//! per axis, the benchmark ID, Criterion's mean, median and standard deviation with their
+//! confidence intervals (in nanoseconds), and the throughputs, latency percentiles and metric
+//! means from the report.
+//!
+//! ```no_run
+//! use bench_matrix::export::ResultsTable;
+//! use bench_matrix::SuiteReport;
+//!
+//! // Usually the report returned by `SyncBenchmarkSuite::run()`.
+//! let report = SuiteReport { suite_name: "SyncExampleSuite".to_string(), ..SuiteReport::default() };
+//! let table = ResultsTable::from_criterion(&report).expect("failed to read Criterion's results");
+//! table.write("target/bench_matrix/results.csv").expect("failed to write results");
+//! ```
+
+use crate::filter::plain_text;
+use crate::params::{AbstractCombination, MatrixCellValue};
+use crate::report::{SuiteReport, ThroughputReport, VariantReport};
+
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Errors that can occur while exporting results.
+#[derive(Debug)]
+pub enum ExportError {
+  /// A file or directory could not be read or written.
+  Io { path: PathBuf, source: std::io::Error },
+  /// A Criterion file could not be parsed.
+  Parse { path: PathBuf, message: String },
+  /// The output file extension is neither `.csv` nor `.json`.
+  UnsupportedFormat { path: PathBuf },
+  /// A directory or file holds no results to compare.
+  NoResults { path: PathBuf, message: String },
+}
+
+impl fmt::Display for ExportError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ExportError::Io { path, source } => write!(f, "failed to access '{}': {}", path.display(), source),
+      ExportError::Parse { path, message } => write!(f, "invalid Criterion file '{}': {}", path.display(), message),
+      ExportError::UnsupportedFormat { path } => write!(
+        f,
+        "unsupported results file '{}': expected a .csv or .json extension",
+        path.display()
+      ),
+      ExportError::NoResults { path, message } => write!(f, "no results in '{}': {}", path.display(), message),
+    }
+  }
+}
+
+impl std::error::Error for ExportError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      ExportError::Io { source, .. } => Some(source),
+      _ => None,
+    }
+  }
+}
+
+/// One of Criterion's estimates, in nanoseconds, with its confidence interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+  pub point: f64,
+  pub lower: f64,
+  pub upper: f64,
+}
+
+/// The estimates Criterion computed for one benchmark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CriterionEstimates {
+  pub mean: Estimate,
+  pub median: Estimate,
+  pub std_dev: Estimate,
+  /// The confidence level of the intervals, e.g. `0.95`.
+  pub confidence_level: f64,
+}
+
+/// The results of one variant.
+#[derive(Debug, Clone)]
+pub struct ResultsRow {
+  /// The full benchmark ID.
+  pub id: String,
+  pub combination: AbstractCombination,
+  /// Criterion's estimates, or `None` if Criterion has no results for the variant (such as
+  /// open-loop variants).
+  pub estimates: Option<CriterionEstimates>,
+  /// The variant's report.
+  pub report: VariantReport,
+}
+
+impl ResultsRow {
+  /// Returns the mean time per iteration in nanoseconds: Criterion's estimate if available,
+  /// otherwise the report's coarse mean.
+  pub fn mean_ns(&self) -> Option<f64> {
+    self
+      .estimates
+      .map(|estimates| estimates.mean.point)
+      .or_else(|| self.report.run_mean_time().map(|mean| mean.as_secs_f64() * 1e9))
+  }
+
+  /// Returns the rate of one of the report's throughputs, computed from Criterion's mean when
+  /// available since it is more precise than the report's, which includes warm-up.
+  pub(crate) fn per_second(&self, throughput: &ThroughputReport) -> f64 {
+    match self.estimates.map(|estimates| estimates.mean.point / 1e9) {
+      Some(secs) if secs > 0.0 => throughput.per_iteration / secs,
+      _ => throughput.per_second,
+    }
+  }
+
+  /// Returns the row's cells, keyed by column name.
+  fn cells(&self, parameter_names: &[String]) -> Vec<(String, Value)> {
+    let mut cells: Vec<(String, Value)> = parameter_names
+      .iter()
+      .zip(&self.combination.cells)
+      .map(|(name, cell)| (name.clone(), cell_value(cell)))
+      .collect();
+    cells.push(("id".to_string(), Value::String(self.id.clone())));
+
+    if let Some(estimates) = self.estimates {
+      for (name, estimate) in [("mean", estimates.mean), ("median", estimates.median), ("std_dev", estimates.std_dev)] {
+        cells.push((format!("{}_ns", name), number(estimate.point)));
+        cells.push((format!("{}_lower_ns", name), number(estimate.lower)));
+        cells.push((format!("{}_upper_ns", name), number(estimate.upper)));
+      }
+      cells.push(("confidence_level".to_string(), number(estimates.confidence_level)));
+    }
+
+    for throughput in &self.report.throughputs {
+      cells.push((
+        format!("{}_{}_per_sec", throughput.name, throughput.unit),
+        number(self.per_second(throughput)),
+      ));
+    }
+
+    if let Some(open_loop) = &self.report.open_loop {
+      cells.push(("target_rate".to_string(), number(open_loop.target_rate)));
+      cells.push(("achieved_rate".to_string(), number(open_loop.achieved_rate)));
+    }
+    if let Some(latency) = &self.report.latency {
+      for (name, value) in [
+        ("p50", latency.p50),
+        ("p90", latency.p90),
+        ("p99", latency.p99),
+        ("p999", latency.p999),
+        ("max", latency.max),
+      ] {
+        cells.push((format!("latency_{}_ns", name), number(value.as_secs_f64() * 1e9)));
+      }
+    }
+    for metric in &self.report.metrics {
+      cells.push((format!("{}_mean", metric.name), number(metric.mean)));
+    }
+    cells
+  }
+}
+
+/// A suite's results, one row per benchmarked combination.
+#[derive(Debug, Clone)]
+pub struct ResultsTable {
+  pub suite_name: String,
+  /// The axis column names: the suite's parameter names, or `axis_0`, `axis_1`, ... without them.
+  pub parameter_names: Vec<String>,
+  pub rows: Vec<ResultsRow>,
+}
+
+impl ResultsTable {
+  /// Builds the table from the report of a suite run, reading Criterion's estimates from its
+  /// default output directory (see [`criterion_output_dir`]).
+  pub fn from_criterion(report: &SuiteReport) -> Result<Self, ExportError> {
+    Self::from_criterion_dir(report, criterion_output_dir())
+  }
+
+  /// Builds the table from the report of a suite run, reading Criterion's estimates from
+  /// `criterion_dir`. Variants without estimates get empty estimate columns.
+  pub fn from_criterion_dir(report: &SuiteReport, criterion_dir: impl AsRef<Path>) -> Result<Self, ExportError> {
+    let mut benchmark_dirs = HashMap::new();
+    let criterion_dir = criterion_dir.as_ref();
+    if criterion_dir.is_dir() {
+      index_benchmarks(criterion_dir, "new", &mut benchmark_dirs)?;
+    }
+
+    let rows = report
+      .variants
+      .iter()
+      .map(|variant| {
+        let estimates = match benchmark_dirs.get(&variant.id) {
+          Some(dir) => Some(read_estimates(&dir.join("estimates.json"))?),
+          None => None,
+        };
+        Ok(ResultsRow {
+          id: variant.id.clone(),
+          combination: variant.combination.clone(),
+          estimates,
+          report: variant.clone(),
+        })
+      })
+      .collect::<Result<Vec<_>, ExportError>>()?;
+
+    let axis_count = report.variants.first().map_or(0, |variant| variant.combination.cells.len());
+    let parameter_names = match &report.parameter_names {
+      Some(names) if report.variants.is_empty() || names.len() == axis_count => names.clone(),
+      _ => (0..axis_count).map(|index| format!("axis_{}", index)).collect(),
+    };
+    Ok(Self {
+      suite_name: report.suite_name.clone(),
+      parameter_names,
+      rows,
+    })
+  }
+
+  /// Returns the row of the variant with the given full benchmark ID.
+  pub fn row(&self, id: &str) -> Option<&ResultsRow> {
+    self.rows.iter().find(|row| row.id == id)
+  }
+
+  /// Returns the column names, in order: the axes, `id`, then every value column that at
+  /// least one row has.
+  pub fn columns(&self) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for row in &self.rows {
+      for (name, _) in row.cells(&self.parameter_names) {
+        if !columns.contains(&name) {
+          columns.push(name);
+        }
+      }
+    }
+    if columns.is_empty() {
+      columns = self.parameter_names.clone();
+      columns.push("id".to_string());
+    }
+    columns
+  }
+
+  /// Renders the table as CSV with a header line. Missing values are empty fields.
+  pub fn to_csv(&self) -> String {
+    let columns = self.columns();
+    let mut out = csv_line(columns.iter().map(String::as_str));
+    for row in &self.rows {
+      let cells: HashMap<String, Value> = row.cells(&self.parameter_names).into_iter().collect();
+      let fields: Vec<String> = columns
+        .iter()
+        .map(|column| match cells.get(column) {
+          None | Some(Value::Null) => String::new(),
+          Some(Value::String(text)) => text.clone(),
+          Some(other) => other.to_string(),
+        })
+        .collect();
+      out.push_str(&csv_line(fields.iter().map(String::as_str)));
+    }
+    out
+  }
+
+  /// Renders the table as a JSON array with one object per row. Axis values keep their type;
+  /// missing values are `null`.
+  pub fn to_json(&self) -> String {
+    let columns = self.columns();
+    let rows: Vec<Value> = self
+      .rows
+      .iter()
+      .map(|row| {
+        let mut cells: HashMap<String, Value> = row.cells(&self.parameter_names).into_iter().collect();
+        let object: Map<String, Value> = columns
+          .iter()
+          .map(|column| (column.clone(), cells.remove(column).unwrap_or(Value::Null)))
+          .collect();
+        Value::Object(object)
+      })
+      .collect();
+    serde_json::to_string_pretty(&rows).expect("JSON values always serialize")
+  }
+
+  /// Writes the table to `path` as CSV or JSON, chosen by its extension, creating the parent
+  /// directory if needed.
+  pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ExportError> {
+    let path = path.as_ref();
+    let contents = match path.extension().and_then(|extension| extension.to_str()) {
+      Some(extension) if extension.eq_ignore_ascii_case("csv") => self.to_csv(),
+      Some(extension) if extension.eq_ignore_ascii_case("json") => self.to_json(),
+      _ => return Err(ExportError::UnsupportedFormat { path: path.to_path_buf() }),
+    };
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+      std::fs::create_dir_all(parent).map_err(|source| ExportError::Io {
+        path: parent.to_path_buf(),
+        source,
+      })?;
+    }
+    std::fs::write(path, contents).map_err(|source| ExportError::Io {
+      path: path.to_path_buf(),
+      source,
+    })
+  }
+}
+
+/// Returns the directory Criterion writes its results to, resolved as Criterion does:
+/// `$CRITERION_HOME`, then `$CARGO_TARGET_DIR/criterion`, then the target directory reported
+/// by `cargo metadata`, then `target/criterion`.
+pub fn criterion_output_dir() -> PathBuf {
+  #[derive(Deserialize)]
+  struct Metadata {
+    target_directory: PathBuf,
+  }
+
+  if let Some(home) = std::env::var_os("CRITERION_HOME") {
+    return PathBuf::from(home);
+  }
+  std::env::var_os("CARGO_TARGET_DIR")
+    .map(PathBuf::from)
+    .or_else(|| {
+      let output = std::process::Command::new(std::env::var_os("CARGO")?)
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()
+        .ok()?;
+      let metadata: Metadata = serde_json::from_slice(&output.stdout).ok()?;
+      Some(metadata.target_directory)
+    })
+    .map(|target| target.join("criterion"))
+    .unwrap_or_else(|| PathBuf::from("target/criterion"))
+}
+
+#[derive(Deserialize)]
+struct BenchmarkFile {
+  full_id: String,
+}
+
+#[derive(Deserialize)]
+struct EstimatesFile {
+  mean: EstimateFile,
+  median: EstimateFile,
+  std_dev: EstimateFile,
+}
+
+#[derive(Deserialize)]
+struct EstimateFile {
+  confidence_interval: ConfidenceIntervalFile,
+  point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct ConfidenceIntervalFile {
+  confidence_level: f64,
+  lower_bound: f64,
+  upper_bound: f64,
+}
+
+impl From<&EstimateFile> for Estimate {
+  fn from(file: &EstimateFile) -> Self {
+    Estimate {
+      point: file.point_estimate,
+      lower: file.confidence_interval.lower_bound,
+      upper: file.confidence_interval.upper_bound,
+    }
+  }
+}
+
+/// Maps the full ID of every benchmark under `dir` to its directory for `baseline` (`new` for
+/// the latest run). Criterion makes IDs filename-safe and deduplicates directory names, so the
+/// ID recorded in each `benchmark.json` is used rather than a path derived from the ID.
+pub(crate) fn index_benchmarks(dir: &Path, baseline: &str, found: &mut HashMap<String, PathBuf>) -> Result<(), ExportError> {
+  let io_error = |source| ExportError::Io {
+    path: dir.to_path_buf(),
+    source,
+  };
+  for entry in std::fs::read_dir(dir).map_err(io_error)? {
+    let path = entry.map_err(io_error)?.path();
+    if !path.is_dir() {
+      continue;
+    }
+    let benchmark_file = path.join("benchmark.json");
+    if path.file_name().is_some_and(|name| name == baseline) && benchmark_file.is_file() {
+      let benchmark: BenchmarkFile = read_json(&benchmark_file)?;
+      found.insert(benchmark.full_id, path);
+    } else {
+      index_benchmarks(&path, baseline, found)?;
+    }
+  }
+  Ok(())
+}
+
+pub(crate) fn read_estimates(path: &Path) -> Result<CriterionEstimates, ExportError> {
+  let file: EstimatesFile = read_json(path)?;
+  Ok(CriterionEstimates {
+    mean: (&file.mean).into(),
+    median: (&file.median).into(),
+    std_dev: (&file.std_dev).into(),
+    confidence_level: file.mean.confidence_interval.confidence_level,
+  })
+}
+
+pub(crate) fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, ExportError> {
+  let text = std::fs::read_to_string(path).map_err(|source| ExportError::Io {
+    path: path.to_path_buf(),
+    source,
+  })?;
+  serde_json::from_str(&text).map_err(|e| ExportError::Parse {
+    path: path.to_path_buf(),
+    message: e.to_string(),
+  })
+}
+
+fn cell_value(cell: &MatrixCellValue) -> Value {
+  match cell {
+    MatrixCellValue::Int(i) => Value::from(*i),
+    MatrixCellValue::Unsigned(u) => Value::from(*u),
+    MatrixCellValue::Bool(b) => Value::Bool(*b),
+    other => Value::String(plain_text(other)),
+  }
+}
+
+fn number(value: f64) -> Value {
+  Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+fn csv_line<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+  let mut line = fields
+    .map(|field| {
+      if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+      } else {
+        field.to_string()
+      }
+    })
+    .collect::<Vec<_>>()
+    .join(",");
+  line.push('\n');
+  line
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  fn write_benchmark(root: &Path, dir: &str, full_id: &str, mean: f64) {
+    let new_dir = root.join(dir).join("new");
+    std::fs::create_dir_all(&new_dir).unwrap();
+    std::fs::write(new_dir.join("benchmark.json"), format!(r#"{{"group_id":"x","full_id":"{}"}}"#, full_id)).unwrap();
+    let estimate = |point: f64| {
+      format!(
+        r#"{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{},"upper_bound":{}}},"point_estimate":{},"standard_error":1.0}}"#,
+        point - 1.0,
+        point + 1.0,
+        point
+      )
+    };
+    std::fs::write(
+      new_dir.join("estimates.json"),
+      format!(r#"{{"mean":{},"median":{},"std_dev":{}}}"#, estimate(mean), estimate(mean), estimate(2.0)),
+    )
+    .unwrap();
+  }
+
+  fn variant(id: &str, backend: &str, size: u64) -> VariantReport {
+    VariantReport {
+      id: id.to_string(),
+      combination: AbstractCombination {
+        cells: vec![MatrixCellValue::Tag(backend.to_string()), MatrixCellValue::Unsigned(size)],
+      },
+      iterations: 10,
+      total_time: Duration::from_micros(10),
+      throughputs: vec![ThroughputReport {
+        name: "throughput".to_string(),
+        unit: "bytes".to_string(),
+        per_iteration: size as f64,
+        per_second: 1_000_000.0,
+      }],
+      metrics: vec![],
+      latency: None,
+      open_loop: None,
+    }
+  }
+
+  #[test]
+  fn test_rows_are_keyed_by_axis_values() {
+    let root = std::env::temp_dir().join(format!("bench_matrix_export_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    write_benchmark(&root, "Io/Backend-Uring_Size-64", "Io/Backend-Uring_Size-64", 500.0);
+    write_benchmark(&root, "Io/Backend-a_b_Size-64", "Io/Backend-a,b_Size-64", 1000.0);
+
+    let report = SuiteReport {
+      suite_name: "Io".to_string(),
+      parameter_names: Some(vec!["Backend".to_string(), "Size".to_string()]),
+      variants: vec![
+        variant("Io/Backend-Uring_Size-64", "Uring", 64),
+        variant("Io/Backend-a,b_Size-64", "a,b", 64),
+        variant("Io/Backend-Epoll_Size-64", "Epoll", 64),
+      ],
+    };
+    let table = ResultsTable::from_criterion_dir(&report, &root).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(table.row("Io/Backend-Uring_Size-64").unwrap().mean_ns(), Some(500.0));
+    assert!(table.row("Io/Backend-Epoll_Size-64").unwrap().estimates.is_none());
+    let csv = table.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+      lines[0],
+      "Backend,Size,id,mean_ns,mean_lower_ns,mean_upper_ns,median_ns,median_lower_ns,median_upper_ns,std_dev_ns,std_dev_lower_ns,std_dev_upper_ns,confidence_level,throughput_bytes_per_sec"
+    );
+    assert_eq!(lines[1], "Uring,64,Io/Backend-Uring_Size-64,500.0,499.0,501.0,500.0,499.0,501.0,2.0,1.0,3.0,0.95,128000000.0");
+    assert!(lines[2].starts_with("\"a,b\",64,\"Io/Backend-a,b_Size-64\",1000.0,"), "{}", lines[2]);
+    assert_eq!(lines[3], "Epoll,64,Io/Backend-Epoll_Size-64,,,,,,,,,,,1000000.0");
+
+    let json: Value = serde_json::from_str(&table.to_json()).unwrap();
+    assert_eq!(json[0]["Size"], Value::from(64u64));
+    assert_eq!(json[2]["mean_ns"], Value::Null);
+  }
+}
@@ -0,0 +1,342 @@
+use crate::params::MatrixCellValue;
+
+/// Name of the environment variable read by the suites to narrow or override axes.
+pub const FILTER_ENV_VAR: &str = "BENCH_MATRIX_FILTER";
+
+/// Command-line flag recognised by [`MatrixFilter::from_args`].
+pub const FILTER_ARG: &str = "--matrix";
+
+/// A selection of axis values used to narrow (or override) a suite's parameter axes
+/// before any combination is generated.
+///
+/// A filter is written as comma-separated `Axis=value` clauses, where several values for
+/// one axis are separated by `|`, e.g. `Backend=Uring,PktSize=64|1024`. Values are matched
+/// against the plain text of the axis values (`Uring` for `Tag("Uring")`, `64` for
+/// `Unsigned(64)`, `true` for `Bool(true)`). A value prefixed with `+` may be off the axis: it
+/// is parsed with the same type as the axis' existing values, which allows sweeping a value
+/// the benchmark binary was not compiled with (`PktSize=+2048`). Without the prefix, a value
+/// that is not on the axis is an error, so a typo cannot silently run a made-up variant.
+///
+/// Because narrowing happens on the axes themselves, filtered-out combinations never reach
+/// the extractor or the global setup functions.
+///
+/// # Example
+/// ```
+/// # use bench_matrix::{MatrixCellValue, MatrixFilter};
+/// let names = vec!["Backend".to_string(), "PktSize".to_string()];
+/// let axes = vec![
+///   vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Tag("Epoll".to_string())],
+///   vec![MatrixCellValue::Unsigned(64), MatrixCellValue::Unsigned(512)],
+/// ];
+///
+/// let filter = MatrixFilter::parse("Backend=Uring,PktSize=64|+2048").unwrap();
+/// let narrowed = filter.apply(&names, &axes).unwrap();
+/// assert_eq!(narrowed[0], vec![MatrixCellValue::Tag("Uring".to_string())]);
+/// assert_eq!(narrowed[1], vec![MatrixCellValue::Unsigned(64), MatrixCellValue::Unsigned(2048)]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatrixFilter {
+  clauses: Vec<(String, Vec<String>)>,
+}
+
+impl MatrixFilter {
+  /// Parses a filter specification such as `Backend=Uring,PktSize=64|1024`.
+  pub fn parse(spec: &str) -> Result<Self, String> {
+    let mut clauses: Vec<(String, Vec<String>)> = Vec::new();
+    for clause in spec.split(',').map(str::trim).filter(|clause| !clause.is_empty()) {
+      let (axis, values) = clause
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid filter clause '{}': expected 'Axis=value[|value...]'", clause))?;
+      let axis = axis.trim();
+      if axis.is_empty() {
+        return Err(format!("Invalid filter clause '{}': missing axis name", clause));
+      }
+      let values: Vec<String> = values.split('|').map(|value| value.trim().to_string()).collect();
+      if values.iter().any(|value| value.trim_start_matches('+').is_empty()) {
+        return Err(format!("Invalid filter clause '{}': empty value", clause));
+      }
+      if clauses.iter().any(|(existing, _)| existing == axis) {
+        return Err(format!("Invalid filter: axis '{}' is listed more than once", axis));
+      }
+      clauses.push((axis.to_string(), values));
+    }
+    Ok(Self { clauses })
+  }
+
+  /// Reads the filter from the `BENCH_MATRIX_FILTER` environment variable.
+  /// Returns `Ok(None)` if the variable is unset or empty.
+  pub fn from_env() -> Result<Option<Self>, String> {
+    match std::env::var(FILTER_ENV_VAR) {
+      Ok(spec) if !spec.trim().is_empty() => Self::parse(&spec)
+        .map(Some)
+        .map_err(|e| format!("{} (from {})", e, FILTER_ENV_VAR)),
+      _ => Ok(None),
+    }
+  }
+
+  /// Collects every `--matrix <spec>` / `--matrix=<spec>` argument into one filter.
+  /// Returns `Ok(None)` if no such argument is present.
+  ///
+  /// `StandaloneSuite` reads the process arguments with this, next to the environment variable.
+  /// Binaries built with `criterion_main!` (or `bench_matrix!`) cannot take the flag, because
+  /// Criterion's argument parser rejects unknown flags before any suite runs; filter their
+  /// suites with `BENCH_MATRIX_FILTER`.
+  pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+    let mut specs = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+      if arg == FILTER_ARG {
+        specs.push(
+          args
+            .next()
+            .ok_or_else(|| format!("Missing filter specification after '{}'", FILTER_ARG))?,
+        );
+      } else if let Some(spec) = arg.strip_prefix(FILTER_ARG).and_then(|rest| rest.strip_prefix('=')) {
+        specs.push(spec.to_string());
+      }
+    }
+    if specs.is_empty() {
+      return Ok(None);
+    }
+    Self::parse(&specs.join(",")).map(Some)
+  }
+
+  /// Returns the `(axis name, textual values)` clauses of this filter.
+  pub fn clauses(&self) -> &[(String, Vec<String>)] {
+    &self.clauses
+  }
+
+  /// Returns whether this filter has no clauses (and so leaves every axis unchanged).
+  pub fn is_empty(&self) -> bool {
+    self.clauses.is_empty()
+  }
+
+  /// Applies the filter to `axes`, returning the narrowed (or overridden) axes.
+  ///
+  /// Clauses naming an axis that is not in `param_names` are reported as an error, as are
+  /// values that are not on the axis (unless prefixed with `+`) and `+` values that do not
+  /// parse as the axis' value type.
+  pub fn apply(&self, param_names: &[String], axes: &[Vec<MatrixCellValue>]) -> Result<Vec<Vec<MatrixCellValue>>, String> {
+    let mut filtered = axes.to_vec();
+    for (axis_name, values) in &self.clauses {
+      let index = param_names.iter().position(|name| name == axis_name).ok_or_else(|| {
+        format!(
+          "Filter references unknown axis '{}' (known axes: {})",
+          axis_name,
+          param_names.join(", ")
+        )
+      })?;
+      let axis = axes
+        .get(index)
+        .ok_or_else(|| format!("Filter axis '{}' has no values defined", axis_name))?;
+
+      let mut selected = Vec::with_capacity(values.len());
+      for text in values {
+        let (text, sweep_off_axis) = match text.strip_prefix('+') {
+          Some(text) => (text, true),
+          None => (text.as_str(), false),
+        };
+        let cell = match axis.iter().find(|cell| plain_text(cell) == text) {
+          Some(cell) => cell.clone(),
+          None if sweep_off_axis => {
+            parse_like(axis.first(), text).map_err(|e| format!("Filter value for axis '{}': {}", axis_name, e))?
+          }
+          None => {
+            return Err(format!(
+              "Filter value '{}' is not on axis '{}' (values: {}); write '+{}' to sweep it anyway",
+              text,
+              axis_name,
+              axis.iter().map(plain_text).collect::<Vec<_>>().join(", "),
+              text
+            ));
+          }
+        };
+        if !selected.contains(&cell) {
+          selected.push(cell);
+        }
+      }
+      filtered[index] = selected;
+    }
+    Ok(filtered)
+  }
+}
+
+/// Applies the suite's explicit `filters`, followed by the `BENCH_MATRIX_FILTER` environment
+/// filter and the `--matrix` command-line flags if set, to `axes`. Used by the suites before
+/// generating combinations; ambient clauses the suite cannot apply are reported in `warnings`.
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+pub(crate) fn apply_filters(
+  filters: &[MatrixFilter],
+  param_names: Option<&[String]>,
+  axes: &[Vec<MatrixCellValue>],
+  warnings: &mut Vec<String>,
+) -> Result<Vec<Vec<MatrixCellValue>>, String> {
+  let ambient = [
+    (FILTER_ENV_VAR, MatrixFilter::from_env()?),
+    (FILTER_ARG, MatrixFilter::from_args(std::env::args().skip(1))?),
+  ];
+  let ambient = ambient
+    .iter()
+    .filter_map(|(source, filter)| filter.as_ref().map(|filter| (*source, filter)));
+  apply_filters_with(filters, ambient, param_names, axes, warnings)
+}
+
+/// Applies `filters`, which must only name axes the suite has, then the `ambient` filters
+/// (each with the source it was read from). Those apply to every suite in the binary, so their
+/// clauses naming an axis the suite does not have are skipped with a warning, as are all of them
+/// for a suite without parameter names.
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+fn apply_filters_with<'f>(
+  filters: &[MatrixFilter],
+  ambient: impl IntoIterator<Item = (&'f str, &'f MatrixFilter)>,
+  param_names: Option<&[String]>,
+  axes: &[Vec<MatrixCellValue>],
+  warnings: &mut Vec<String>,
+) -> Result<Vec<Vec<MatrixCellValue>>, String> {
+  let mut filtered = axes.to_vec();
+  let explicit: Vec<&MatrixFilter> = filters.iter().filter(|filter| !filter.is_empty()).collect();
+  if !explicit.is_empty() {
+    let names = param_names.ok_or_else(|| "Matrix filters require parameter names".to_string())?;
+    for filter in explicit {
+      filtered = filter.apply(names, &filtered)?;
+    }
+  }
+  for (source, filter) in ambient {
+    let (relevant, unmatched): (Vec<_>, Vec<_>) = filter
+      .clauses
+      .iter()
+      .cloned()
+      .partition(|(axis, _)| param_names.is_some_and(|names| names.contains(axis)));
+    for (axis, _) in unmatched {
+      let known = match param_names {
+        Some(names) => format!("axes: {}", names.join(", ")),
+        None => "the suite has no parameter names".to_string(),
+      };
+      warnings.push(format!(
+        "Filter clause on '{}' from {} names no axis of this suite ({}). It will be ignored for this suite",
+        axis, source, known
+      ));
+    }
+    if let Some(names) = param_names {
+      filtered = MatrixFilter { clauses: relevant }.apply(names, &filtered)?;
+    }
+  }
+  Ok(filtered)
+}
+
+/// The unadorned text of a cell, as written in a filter specification.
+pub(crate) fn plain_text(cell: &MatrixCellValue) -> String {
+  match cell {
+    MatrixCellValue::Tag(s) | MatrixCellValue::String(s) => s.clone(),
+    MatrixCellValue::Int(i) => i.to_string(),
+    MatrixCellValue::Unsigned(u) => u.to_string(),
+    MatrixCellValue::Bool(b) => b.to_string(),
+  }
+}
+
+/// Parses `text` as a value of the same variant as `template` (a `Tag` if there is none).
+fn parse_like(template: Option<&MatrixCellValue>, text: &str) -> Result<MatrixCellValue, String> {
+  match template {
+    None | Some(MatrixCellValue::Tag(_)) => Ok(MatrixCellValue::Tag(text.to_string())),
+    Some(MatrixCellValue::String(_)) => Ok(MatrixCellValue::String(text.to_string())),
+    Some(MatrixCellValue::Int(_)) => text
+      .parse()
+      .map(MatrixCellValue::Int)
+      .map_err(|_| format!("'{}' is not on the axis and is not a valid Int", text)),
+    Some(MatrixCellValue::Unsigned(_)) => text
+      .parse()
+      .map(MatrixCellValue::Unsigned)
+      .map_err(|_| format!("'{}' is not on the axis and is not a valid Unsigned", text)),
+    Some(MatrixCellValue::Bool(_)) => text
+      .parse()
+      .map(MatrixCellValue::Bool)
+      .map_err(|_| format!("'{}' is not on the axis and is not a valid Bool", text)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn names() -> Vec<String> {
+    vec!["Backend".to_string(), "PktSize".to_string()]
+  }
+
+  fn axes() -> Vec<Vec<MatrixCellValue>> {
+    vec![
+      vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Tag("Epoll".to_string())],
+      vec![MatrixCellValue::Unsigned(64), MatrixCellValue::Unsigned(1024)],
+    ]
+  }
+
+  #[test]
+  fn test_parse_rejects_malformed_specs() {
+    assert!(MatrixFilter::parse("Backend").is_err());
+    assert!(MatrixFilter::parse("=Uring").is_err());
+    assert!(MatrixFilter::parse("Backend=Uring|").is_err());
+    assert!(MatrixFilter::parse("Backend=Uring,Backend=Epoll").is_err());
+    assert!(MatrixFilter::parse("").unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_apply_narrows_and_overrides() {
+    let filter = MatrixFilter::parse("PktSize=1024|+4096").unwrap();
+    let filtered = filter.apply(&names(), &axes()).unwrap();
+    assert_eq!(filtered[0], axes()[0]);
+    assert_eq!(
+      filtered[1],
+      vec![MatrixCellValue::Unsigned(1024), MatrixCellValue::Unsigned(4096)]
+    );
+
+    let err = MatrixFilter::parse("PktSize=+big").unwrap().apply(&names(), &axes()).unwrap_err();
+    assert!(err.contains("not a valid Unsigned"), "unexpected error: {}", err);
+
+    let err = MatrixFilter::parse("Backend=Urng").unwrap().apply(&names(), &axes()).unwrap_err();
+    assert_eq!(
+      err,
+      "Filter value 'Urng' is not on axis 'Backend' (values: Uring, Epoll); write '+Urng' to sweep it anyway"
+    );
+
+    let err = MatrixFilter::parse("Backnd=Uring").unwrap().apply(&names(), &axes()).unwrap_err();
+    assert!(err.contains("unknown axis 'Backnd'"), "unexpected error: {}", err);
+  }
+
+  #[test]
+  fn test_from_args_collects_all_matrix_flags() {
+    let args = ["bench", "--matrix", "Backend=Uring", "--bench", "--matrix=PktSize=64"]
+      .iter()
+      .map(|arg| arg.to_string());
+    let filter = MatrixFilter::from_args(args).unwrap().unwrap();
+    assert_eq!(
+      filter.clauses(),
+      &[
+        ("Backend".to_string(), vec!["Uring".to_string()]),
+        ("PktSize".to_string(), vec!["64".to_string()]),
+      ]
+    );
+    assert_eq!(MatrixFilter::from_args(vec!["bench".to_string()]).unwrap(), None);
+  }
+
+  #[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+  #[test]
+  fn test_ambient_filters_ignore_axes_the_suite_lacks() {
+    let args = MatrixFilter::from_args(["--matrix=Backend=Epoll,Codec=Zstd".to_string()]).unwrap().unwrap();
+    let ambient = || [(FILTER_ARG, &args)];
+    let mut warnings = Vec::new();
+    let filtered = apply_filters_with(&[], ambient(), Some(&names()), &axes(), &mut warnings).unwrap();
+    assert_eq!(filtered[0], vec![MatrixCellValue::Tag("Epoll".to_string())]);
+    assert_eq!(filtered[1], axes()[1]);
+    assert_eq!(
+      warnings,
+      ["Filter clause on 'Codec' from --matrix names no axis of this suite (axes: Backend, PktSize). It will be ignored for this suite"]
+    );
+    // A suite without parameter names is left alone by ambient filters, with a warning per clause...
+    let mut warnings = Vec::new();
+    assert_eq!(apply_filters_with(&[], ambient(), None, &axes(), &mut warnings).unwrap(), axes());
+    assert_eq!(warnings.len(), 2);
+    // ...but a filter set on the suite itself must name its axes.
+    let explicit = MatrixFilter::parse("Codec=Zstd").unwrap();
+    assert!(apply_filters_with(&[explicit], ambient(), Some(&names()), &axes(), &mut Vec::new()).is_err());
+  }
+
+}
@@ -108,8 +108,9 @@ impl<'a> ExactSizeIterator for CombinationIterator<'a> {
 /// the returned iterator will be empty (i.e., its `.len()` will be 0).
 pub fn generate_combinations(axes: &[Vec<MatrixCellValue>]) -> CombinationIterator<'_> {
   // The length of a Cartesian product is the product of the lengths of the input sets.
-  // If any set is empty, the entire product is empty.
-  let len = if axes.iter().any(Vec::is_empty) {
+  // If any set is empty, the entire product is empty; with no sets at all, `multi_cartesian_product`
+  // yields nothing either, so the product of zero lengths (1) must not be reported.
+  let len = if axes.is_empty() || axes.iter().any(Vec::is_empty) {
     0
   } else {
     axes.iter().map(Vec::len).product()
@@ -141,6 +142,13 @@ mod tests {
     assert_eq!(iter.count(), 0, "Iterator should yield 0 items");
   }
 
+  #[test]
+  fn test_size_hint_matches_items_for_empty_axes() {
+    let axes: Vec<Vec<MatrixCellValue>> = vec![];
+    assert_eq!(generate_combinations(&axes).size_hint(), (0, Some(0)));
+    assert!(get_all_combos(&axes).is_empty());
+  }
+
   #[test]
   fn test_len_calculation_and_iteration_one_axis_empty() {
     let axis1 = vec![MatrixCellValue::Tag("A".to_string())];
@@ -0,0 +1,688 @@
+//! Benchmark ID formats, including an injective and reversible encoding of combinations.
+//!
+//! The default (readable) IDs produced by `AbstractCombination::id_suffix` and
+//! `id_suffix_with_names` are lossy: `String("a b")` and `String("a_b")` both become `a_b`,
+//! and with names `Int(5)` and `Unsigned(5)` both become `5`. Criterion would then merge or
+//! reject the colliding variants. The reversible encoding defined here never maps two distinct
+//! combinations to the same ID, and only uses characters that are safe in file names.
+//!
+//! # Encoding
+//!
+//! Text is *escaped* by keeping ASCII letters and digits (and `-`, inside values) and writing
+//! every other UTF-8 byte as `~` followed by two uppercase hex digits (`_` becomes `~5F`,
+//! a space `~20`). Each cell is then encoded as:
+//!
+//! | Cell              | Encoding                      | Example                  |
+//! |-------------------|-------------------------------|--------------------------|
+//! | `Unsigned(u)`     | decimal digits                | `512`                    |
+//! | `Int(i)`          | sign and digits               | `+5`, `-5`               |
+//! | `Bool(b)`         | `true` / `false`              | `true`                   |
+//! | `String(s)`       | `~s` + escaped text           | `~sa~20b`                |
+//! | `Tag(s)`          | escaped text                  | `Uring`, `HWM-Low`       |
+//!
+//! A `Tag` whose escaped text would be empty, start with a digit, `+` or `-`, or read as
+//! `true`/`false` is prefixed with `~t` (e.g. `Tag("5")` is `~t5`). Markers use a lowercase
+//! letter after `~` and escapes an uppercase hex digit, so the two never clash.
+//!
+//! Cells are joined with `_`. With parameter names, each cell is written as
+//! `<escaped name>-<cell>`, where names escape `-` too so the first `-` always separates the
+//! name from the value. A combination without cells encodes as `_`.
+//!
+//! # Parsing
+//!
+//! [`IdFormat::parse`] turns a benchmark ID (the part after the group name, e.g. the
+//! `value_str` recorded in Criterion's `benchmark.json`) back into an [`AbstractCombination`].
+//! Reversible IDs decode on their own. Readable and [template](IdTemplate) IDs are matched
+//! against the suite's axes and rejected as ambiguous if more than one combination renders to
+//! the same ID. Criterion shortens long result *directory* names, so prefer the ID stored
+//! inside the directory.
+
+use crate::filter::plain_text;
+use crate::generator::generate_combinations;
+use crate::params::{AbstractCombination, MatrixCellValue};
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// How a suite turns a combination into its Criterion benchmark ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum IdFormat {
+  /// The readable, lossy IDs of `id_suffix` / `id_suffix_with_names`
+  /// (e.g. `Backend-Uring_BlockSize-512`). Colliding IDs are detected and refused.
+  #[default]
+  Readable,
+  /// The injective, reversible encoding described in the [module documentation](self)
+  /// (e.g. `Backend-Uring_Mode-~sLow_Offset-+5`).
+  Reversible,
+  /// A user-defined layout with optional per-value labels (e.g. `Uring/1MiB/x4`).
+  /// See [`IdTemplate`].
+  Template(IdTemplate),
+}
+
+impl IdFormat {
+  /// Formats `combination` (without a leading `_`) according to this format.
+  pub fn format(&self, combination: &AbstractCombination, param_names: Option<&[String]>) -> String {
+    match self {
+      IdFormat::Readable => {
+        let suffix = match param_names {
+          Some(names) => combination.id_suffix_with_names(names),
+          None => combination.id_suffix(),
+        };
+        suffix.strip_prefix('_').unwrap_or("").to_string()
+      }
+      IdFormat::Reversible => encode_combination(combination, param_names),
+      IdFormat::Template(template) => match param_names {
+        Some(names) if template.check_axes(Some(names)).is_ok() => template.render(combination, names),
+        _ => IdFormat::Readable.format(combination, param_names),
+      },
+    }
+  }
+
+  /// Parses a benchmark ID produced by this format back into a combination with typed cells.
+  ///
+  /// `param_names` must be the names the ID was generated with (or `None` if there were none)
+  /// and `axes` the suite's parameter axes. Reversible IDs only need `axes` for their length,
+  /// so they also decode values that a filter swept in; readable IDs must be made of values
+  /// that are on the axes. A leading `_`, as returned by `id_suffix`, is accepted.
+  pub fn parse(
+    &self,
+    id: &str,
+    param_names: Option<&[String]>,
+    axes: &[Vec<MatrixCellValue>],
+  ) -> Result<AbstractCombination, String> {
+    if let Some(names) = param_names
+      && names.len() != axes.len()
+    {
+      return Err(format!(
+        "{} parameter names were given for {} parameter axes",
+        names.len(),
+        axes.len()
+      ));
+    }
+    match self {
+      IdFormat::Reversible => {
+        let combination = decode_combination(id, param_names)?;
+        if combination.cells.len() != axes.len() {
+          return Err(format!(
+            "ID '{}' has {} values but the suite has {} axes",
+            id,
+            combination.cells.len(),
+            axes.len()
+          ));
+        }
+        Ok(combination)
+      }
+      IdFormat::Readable | IdFormat::Template(_) => {
+        let mut matches = Vec::new();
+        if let IdFormat::Template(template) = self {
+          template.check_axes(param_names)?;
+          matches.extend(
+            generate_combinations(axes)
+              .filter(|combination| self.format(combination, param_names) == id)
+              .map(|combination| combination.cells),
+          );
+        } else {
+          match_readable(id, param_names, axes, &mut Vec::new(), &mut matches);
+          if matches.is_empty()
+            && let Some(stripped) = id.strip_prefix('_')
+          {
+            match_readable(stripped, param_names, axes, &mut Vec::new(), &mut matches);
+          }
+        }
+        match matches.len() {
+          0 => Err(format!("ID '{}' does not match any combination of the axes", id)),
+          1 => Ok(AbstractCombination {
+            cells: matches.remove(0),
+          }),
+          n => Err(format!(
+            "ID '{}' is ambiguous: {} combinations render to it ({}); consider IdFormat::Reversible",
+            id,
+            n,
+            matches.iter().map(|cells| format!("{:?}", cells)).collect::<Vec<_>>().join(", ")
+          )),
+        }
+      }
+    }
+  }
+}
+
+/// How a template placeholder renders its axis value when no label is set for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueStyle {
+  /// The value's plain text (`Uring`, `512`, `-5`, `true`).
+  Plain,
+  /// An integer byte count in binary units (`512B`, `4KiB`, `1.5MiB`). Written `{Axis:bytes}`.
+  Bytes,
+  /// An integer duration in the given unit, shown in the largest fitting unit
+  /// (`{Axis:ms}` renders `1500` as `1.5s`). Written `{Axis:ns}`, `{Axis:us}`, `{Axis:ms}` or `{Axis:s}`.
+  Duration(DurationUnit),
+}
+
+/// The unit of an integer duration axis rendered with [`ValueStyle::Duration`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationUnit {
+  Nanos,
+  Micros,
+  Millis,
+  Secs,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePiece {
+  Literal(String),
+  Axis { name: String, style: ValueStyle },
+}
+
+/// A benchmark ID layout such as `"{Backend}/{Size:bytes}/x{Threads}"`, with optional
+/// per-value display labels.
+///
+/// Placeholders name an axis, optionally followed by a [`ValueStyle`] (`bytes`, `ns`, `us`,
+/// `ms` or `s`); `{{` and `}}` write literal braces. A label set with [`IdTemplate::label`]
+/// replaces the rendering of that exact value. Templates refer to axes by name, so the suite
+/// must have parameter names; otherwise (or if a placeholder names an unknown axis) the suite
+/// warns and falls back to readable IDs. Labels and styles can make distinct combinations
+/// render the same ID; such duplicates are refused like any other.
+///
+/// # Example
+/// ```
+/// # use bench_matrix::{AbstractCombination, IdFormat, IdTemplate, MatrixCellValue};
+/// let names = vec!["Backend".to_string(), "Size".to_string(), "Threads".to_string()];
+/// let template = IdTemplate::parse("{Backend}/{Size:bytes}/x{Threads}")
+///   .unwrap()
+///   .label("Backend", "Uring", "io_uring");
+/// let combination = AbstractCombination {
+///   cells: vec![
+///     MatrixCellValue::Tag("Uring".to_string()),
+///     MatrixCellValue::Unsigned(1_048_576),
+///     MatrixCellValue::Unsigned(4),
+///   ],
+/// };
+/// assert_eq!(IdFormat::Template(template).format(&combination, Some(&names)), "io_uring/1MiB/x4");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdTemplate {
+  pieces: Vec<TemplatePiece>,
+  labels: Vec<(String, MatrixCellValue, String)>,
+}
+
+impl IdTemplate {
+  /// Parses a template such as `"{Backend}/{Size:bytes}/x{Threads}"`.
+  pub fn parse(template: &str) -> Result<Self, String> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '{' if chars.peek() == Some(&'{') => {
+          chars.next();
+          literal.push('{');
+        }
+        '}' if chars.peek() == Some(&'}') => {
+          chars.next();
+          literal.push('}');
+        }
+        '{' => {
+          let mut placeholder = String::new();
+          loop {
+            match chars.next() {
+              Some('}') => break,
+              Some(c) => placeholder.push(c),
+              None => return Err(format!("Unclosed placeholder in ID template '{}'", template)),
+            }
+          }
+          let (name, style) = match placeholder.split_once(':') {
+            Some((name, style)) => (name.trim(), parse_style(style.trim())?),
+            None => (placeholder.trim(), ValueStyle::Plain),
+          };
+          if name.is_empty() {
+            return Err(format!("Empty placeholder in ID template '{}'", template));
+          }
+          if !literal.is_empty() {
+            pieces.push(TemplatePiece::Literal(std::mem::take(&mut literal)));
+          }
+          pieces.push(TemplatePiece::Axis {
+            name: name.to_string(),
+            style,
+          });
+        }
+        '}' => return Err(format!("Unmatched '}}' in ID template '{}'", template)),
+        c => literal.push(c),
+      }
+    }
+    if !literal.is_empty() {
+      pieces.push(TemplatePiece::Literal(literal));
+    }
+    if !pieces.iter().any(|piece| matches!(piece, TemplatePiece::Axis { .. })) {
+      return Err(format!("ID template '{}' has no placeholders", template));
+    }
+    Ok(Self { pieces, labels: Vec::new() })
+  }
+
+  /// Shows `value` on `axis` as `label` instead of its default rendering.
+  pub fn label(mut self, axis: &str, value: impl Into<MatrixCellValue>, label: &str) -> Self {
+    let value = value.into();
+    self.labels.retain(|(existing_axis, existing_value, _)| existing_axis != axis || *existing_value != value);
+    self.labels.push((axis.to_string(), value, label.to_string()));
+    self
+  }
+
+  /// Checks that every placeholder and label names one of `param_names`.
+  pub fn check_axes(&self, param_names: Option<&[String]>) -> Result<(), String> {
+    let names = param_names.ok_or_else(|| "ID templates require parameter names".to_string())?;
+    let placeholders = self.pieces.iter().filter_map(|piece| match piece {
+      TemplatePiece::Axis { name, .. } => Some(name),
+      TemplatePiece::Literal(_) => None,
+    });
+    for name in placeholders.chain(self.labels.iter().map(|(axis, _, _)| axis)) {
+      if !names.contains(name) {
+        return Err(format!(
+          "ID template references unknown axis '{}' (known axes: {})",
+          name,
+          names.join(", ")
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Renders the template for `combination`. Assumes [`check_axes`](Self::check_axes) passed.
+  fn render(&self, combination: &AbstractCombination, param_names: &[String]) -> String {
+    let mut out = String::new();
+    for piece in &self.pieces {
+      match piece {
+        TemplatePiece::Literal(text) => out.push_str(text),
+        TemplatePiece::Axis { name, style } => {
+          let Some(cell) = param_names
+            .iter()
+            .position(|candidate| candidate == name)
+            .and_then(|index| combination.cells.get(index))
+          else {
+            continue;
+          };
+          match self.labels.iter().find(|(axis, value, _)| axis == name && value == cell) {
+            Some((_, _, label)) => out.push_str(label),
+            None => out.push_str(&render_styled(cell, *style)),
+          }
+        }
+      }
+    }
+    out
+  }
+}
+
+fn parse_style(style: &str) -> Result<ValueStyle, String> {
+  match style {
+    "bytes" => Ok(ValueStyle::Bytes),
+    "ns" => Ok(ValueStyle::Duration(DurationUnit::Nanos)),
+    "us" => Ok(ValueStyle::Duration(DurationUnit::Micros)),
+    "ms" => Ok(ValueStyle::Duration(DurationUnit::Millis)),
+    "s" => Ok(ValueStyle::Duration(DurationUnit::Secs)),
+    other => Err(format!(
+      "Unknown value style '{}' in ID template (expected bytes, ns, us, ms or s)",
+      other
+    )),
+  }
+}
+
+/// Renders a cell with `style`, falling back to its plain text for values the style does not
+/// apply to (non-integers and negative integers).
+fn render_styled(cell: &MatrixCellValue, style: ValueStyle) -> String {
+  let amount = match cell {
+    MatrixCellValue::Unsigned(u) => Some(*u),
+    MatrixCellValue::Int(i) => u64::try_from(*i).ok(),
+    _ => None,
+  };
+  match (style, amount) {
+    (ValueStyle::Bytes, Some(bytes)) => format_bytes(bytes),
+    (ValueStyle::Duration(unit), Some(amount)) => format_duration(match unit {
+      DurationUnit::Nanos => Duration::from_nanos(amount),
+      DurationUnit::Micros => Duration::from_micros(amount),
+      DurationUnit::Millis => Duration::from_millis(amount),
+      DurationUnit::Secs => Duration::from_secs(amount),
+    }),
+    _ => plain_text(cell),
+  }
+}
+
+/// Formats a byte count in binary units, e.g. `512B`, `4KiB`, `1.5MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+  const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+  let mut unit = 0;
+  while unit + 1 < UNITS.len() && bytes >= 1u64 << (10 * (unit + 1)) {
+    unit += 1;
+  }
+  format!("{}{}", format_scaled(bytes as f64 / (1u64 << (10 * unit)) as f64), UNITS[unit])
+}
+
+/// Formats a duration in the largest unit it reaches, e.g. `250ns`, `10us`, `1.5ms`, `2s`.
+pub fn format_duration(duration: Duration) -> String {
+  let nanos = duration.as_nanos();
+  let (scale, unit) = match nanos {
+    n if n >= 1_000_000_000 => (1_000_000_000.0, "s"),
+    n if n >= 1_000_000 => (1_000_000.0, "ms"),
+    n if n >= 1_000 => (1_000.0, "us"),
+    _ => (1.0, "ns"),
+  };
+  format!("{}{}", format_scaled(nanos as f64 / scale), unit)
+}
+
+/// Formats a scaled amount with at most two decimals and no trailing zeros.
+fn format_scaled(amount: f64) -> String {
+  let text = format!("{:.2}", amount);
+  text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Encodes a single cell with the reversible encoding.
+pub fn encode_cell(cell: &MatrixCellValue) -> String {
+  match cell {
+    MatrixCellValue::Unsigned(u) => u.to_string(),
+    MatrixCellValue::Int(i) if *i < 0 => i.to_string(),
+    MatrixCellValue::Int(i) => format!("+{}", i),
+    MatrixCellValue::Bool(b) => b.to_string(),
+    MatrixCellValue::String(s) => format!("~s{}", escape(s, true)),
+    MatrixCellValue::Tag(s) => {
+      let escaped = escape(s, true);
+      let needs_marker = escaped.is_empty()
+        || escaped.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-')
+        || escaped == "true"
+        || escaped == "false";
+      if needs_marker {
+        format!("~t{}", escaped)
+      } else {
+        escaped
+      }
+    }
+  }
+}
+
+/// Encodes a whole combination with the reversible encoding, including parameter names if
+/// given. Names are ignored if their count does not match the number of cells.
+pub fn encode_combination(combination: &AbstractCombination, param_names: Option<&[String]>) -> String {
+  if combination.cells.is_empty() {
+    return "_".to_string();
+  }
+  let names = param_names.filter(|names| names.len() == combination.cells.len());
+  let parts: Vec<String> = combination
+    .cells
+    .iter()
+    .enumerate()
+    .map(|(index, cell)| match names {
+      Some(names) => format!("{}-{}", escape(&names[index], false), encode_cell(cell)),
+      None => encode_cell(cell),
+    })
+    .collect();
+  parts.join("_")
+}
+
+/// Decodes a single cell written with the reversible encoding.
+pub fn decode_cell(text: &str) -> Result<MatrixCellValue, String> {
+  let cell = if let Some(rest) = text.strip_prefix("~s") {
+    MatrixCellValue::String(unescape(rest)?)
+  } else if let Some(rest) = text.strip_prefix("~t") {
+    MatrixCellValue::Tag(unescape(rest)?)
+  } else if text == "true" || text == "false" {
+    MatrixCellValue::Bool(text == "true")
+  } else if text.starts_with(['+', '-']) {
+    MatrixCellValue::Int(text.parse().map_err(|_| format!("'{}' is not a valid Int", text))?)
+  } else if text.starts_with(|c: char| c.is_ascii_digit()) {
+    MatrixCellValue::Unsigned(text.parse().map_err(|_| format!("'{}' is not a valid Unsigned", text))?)
+  } else {
+    MatrixCellValue::Tag(unescape(text)?)
+  };
+  // Only the canonical spelling is accepted, so that decoding stays the exact inverse of encoding.
+  if encode_cell(&cell) != text {
+    return Err(format!("'{}' is not a canonically encoded value", text));
+  }
+  Ok(cell)
+}
+
+/// Decodes a whole combination written with the reversible encoding. If `param_names` is
+/// given, every part must carry the corresponding name.
+pub fn decode_combination(id: &str, param_names: Option<&[String]>) -> Result<AbstractCombination, String> {
+  let id = id.strip_prefix('_').filter(|rest| !rest.is_empty()).unwrap_or(id);
+  if id == "_" || id.is_empty() {
+    return Ok(AbstractCombination { cells: Vec::new() });
+  }
+  let parts: Vec<&str> = id.split('_').collect();
+  let cells = match param_names {
+    None => parts.iter().map(|part| decode_cell(part)).collect::<Result<Vec<_>, _>>()?,
+    Some(names) => {
+      if names.len() != parts.len() {
+        return Err(format!(
+          "ID '{}' has {} values but {} parameter names were given",
+          id,
+          parts.len(),
+          names.len()
+        ));
+      }
+      parts
+        .iter()
+        .zip(names)
+        .map(|(part, name)| {
+          let (encoded_name, value) = part
+            .split_once('-')
+            .ok_or_else(|| format!("'{}' is missing the '{}-' name prefix", part, name))?;
+          if unescape(encoded_name)? != *name {
+            return Err(format!("'{}' does not start with parameter name '{}'", part, name));
+          }
+          decode_cell(value)
+        })
+        .collect::<Result<Vec<_>, _>>()?
+    }
+  };
+  Ok(AbstractCombination { cells })
+}
+
+/// Collects into `matches` every combination of `axes` whose readable ID is `id`.
+fn match_readable(
+  id: &str,
+  param_names: Option<&[String]>,
+  axes: &[Vec<MatrixCellValue>],
+  prefix: &mut Vec<MatrixCellValue>,
+  matches: &mut Vec<Vec<MatrixCellValue>>,
+) {
+  let index = prefix.len();
+  if index == axes.len() {
+    if id.is_empty() {
+      matches.push(prefix.clone());
+    }
+    return;
+  }
+  for cell in &axes[index] {
+    let single = AbstractCombination {
+      cells: vec![cell.clone()],
+    };
+    let suffix = match param_names {
+      Some(names) => single.id_suffix_with_names(&names[index..=index]),
+      None => single.id_suffix(),
+    };
+    let part = &suffix[1..];
+    let Some(rest) = id.strip_prefix(part) else {
+      continue;
+    };
+    let rest = if index + 1 == axes.len() {
+      rest
+    } else {
+      match rest.strip_prefix('_') {
+        Some(rest) => rest,
+        None => continue,
+      }
+    };
+    prefix.push(cell.clone());
+    match_readable(rest, param_names, axes, prefix, matches);
+    prefix.pop();
+  }
+}
+
+/// Escapes `text`, keeping ASCII alphanumerics (and `-` if `keep_dash`) and writing every
+/// other UTF-8 byte as `~XX`.
+fn escape(text: &str, keep_dash: bool) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    if c.is_ascii_alphanumeric() || (keep_dash && c == '-') {
+      out.push(c);
+    } else {
+      let mut buf = [0u8; 4];
+      for byte in c.encode_utf8(&mut buf).bytes() {
+        let _ = write!(out, "~{:02X}", byte);
+      }
+    }
+  }
+  out
+}
+
+/// Reverses [`escape`], rejecting malformed or non-UTF-8 escapes.
+fn unescape(text: &str) -> Result<String, String> {
+  let mut bytes = Vec::with_capacity(text.len());
+  let mut rest = text.as_bytes();
+  while let Some((&byte, tail)) = rest.split_first() {
+    if byte == b'~' {
+      let hex = tail
+        .get(..2)
+        .and_then(|hex| std::str::from_utf8(hex).ok())
+        .filter(|hex| hex.bytes().all(|b| b.is_ascii_digit() || (b'A'..=b'F').contains(&b)))
+        .ok_or_else(|| format!("invalid escape in '{}'", text))?;
+      bytes.push(u8::from_str_radix(hex, 16).map_err(|_| format!("invalid escape in '{}'", text))?);
+      rest = &tail[2..];
+    } else {
+      bytes.push(byte);
+      rest = tail;
+    }
+  }
+  String::from_utf8(bytes).map_err(|_| format!("escapes in '{}' are not valid UTF-8", text))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  fn combo(cells: Vec<MatrixCellValue>) -> AbstractCombination {
+    AbstractCombination { cells }
+  }
+
+  #[test]
+  fn test_encode_cell_examples() {
+    assert_eq!(encode_cell(&MatrixCellValue::Unsigned(512)), "512");
+    assert_eq!(encode_cell(&MatrixCellValue::Int(5)), "+5");
+    assert_eq!(encode_cell(&MatrixCellValue::Int(-5)), "-5");
+    assert_eq!(encode_cell(&MatrixCellValue::Bool(true)), "true");
+    assert_eq!(encode_cell(&MatrixCellValue::String("a b".to_string())), "~sa~20b");
+    assert_eq!(encode_cell(&MatrixCellValue::Tag("HWM-Low".to_string())), "HWM-Low");
+    assert_eq!(encode_cell(&MatrixCellValue::Tag("5".to_string())), "~t5");
+    assert_eq!(encode_cell(&MatrixCellValue::Tag("true".to_string())), "~ttrue");
+    assert_eq!(encode_cell(&MatrixCellValue::Tag("".to_string())), "~t");
+    assert_eq!(encode_cell(&MatrixCellValue::Tag("é".to_string())), "~C3~A9");
+  }
+
+  #[test]
+  fn test_encoding_is_injective_on_lossy_examples() {
+    let cells = vec![
+      MatrixCellValue::String("a b".to_string()),
+      MatrixCellValue::String("a_b".to_string()),
+      MatrixCellValue::Tag("a_b".to_string()),
+      MatrixCellValue::Tag("Int5".to_string()),
+      MatrixCellValue::Tag("5".to_string()),
+      MatrixCellValue::Tag("+5".to_string()),
+      MatrixCellValue::Tag("-5".to_string()),
+      MatrixCellValue::Tag("true".to_string()),
+      MatrixCellValue::String("5".to_string()),
+      MatrixCellValue::String("".to_string()),
+      MatrixCellValue::Tag("".to_string()),
+      MatrixCellValue::Int(5),
+      MatrixCellValue::Int(-5),
+      MatrixCellValue::Unsigned(5),
+      MatrixCellValue::Bool(true),
+    ];
+    let names = vec!["A".to_string()];
+    let encoded: HashSet<String> = cells
+      .iter()
+      .map(|cell| encode_combination(&combo(vec![cell.clone()]), Some(&names)))
+      .collect();
+    assert_eq!(encoded.len(), cells.len());
+
+    // Splitting cells differently must not produce the same ID either.
+    let one = combo(vec![MatrixCellValue::Tag("a_b".to_string())]);
+    let two = combo(vec![MatrixCellValue::Tag("a".to_string()), MatrixCellValue::Tag("b".to_string())]);
+    assert_ne!(encode_combination(&one, None), encode_combination(&two, None));
+  }
+
+  #[test]
+  fn test_id_format_readable_matches_id_suffix() {
+    let names = vec!["Backend".to_string(), "BlockSize".to_string()];
+    let c = combo(vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Unsigned(512)]);
+    assert_eq!(IdFormat::Readable.format(&c, Some(&names)), "Backend-Uring_BlockSize-512");
+    assert_eq!(IdFormat::Readable.format(&c, None), "Uring_Uint512");
+    assert_eq!(IdFormat::Reversible.format(&c, Some(&names)), "Backend-Uring_BlockSize-512");
+    assert_eq!(IdFormat::Reversible.format(&combo(vec![]), None), "_");
+  }
+
+  #[test]
+  fn test_template_rendering_and_parsing() {
+    let names = vec!["Size".to_string(), "Timeout".to_string()];
+    let axes = vec![
+      vec![MatrixCellValue::Unsigned(512), MatrixCellValue::Unsigned(1536), MatrixCellValue::Unsigned(1 << 20)],
+      vec![MatrixCellValue::Unsigned(250), MatrixCellValue::Unsigned(1500)],
+    ];
+    let template = IdTemplate::parse("{Size:bytes}/{Timeout:ms}{{x}}")
+      .unwrap()
+      .label("Size", 512u64, "half-k");
+    let format = IdFormat::Template(template);
+    let rendered: Vec<String> = generate_combinations(&axes)
+      .map(|combination| format.format(&combination, Some(&names)))
+      .collect();
+    assert_eq!(
+      rendered,
+      vec!["half-k/250ms{x}", "half-k/1.5s{x}", "1.5KiB/250ms{x}", "1.5KiB/1.5s{x}", "1MiB/250ms{x}", "1MiB/1.5s{x}"]
+    );
+    let parsed = format.parse("1MiB/1.5s{x}", Some(&names), &axes).unwrap();
+    assert_eq!(parsed.cells, vec![MatrixCellValue::Unsigned(1 << 20), MatrixCellValue::Unsigned(1500)]);
+
+    assert!(IdTemplate::parse("{Size:kb}").is_err());
+    assert!(IdTemplate::parse("{Size").is_err());
+    assert!(IdTemplate::parse("static").is_err());
+    let unknown = IdTemplate::parse("{Sise}").unwrap();
+    assert!(unknown.check_axes(Some(&names)).is_err());
+    assert_eq!(format_duration(Duration::from_nanos(999)), "999ns");
+    assert_eq!(format_bytes(4096), "4KiB");
+  }
+
+  #[test]
+  fn test_reversible_round_trip() {
+    let names = vec!["Mode".to_string(), "Off set".to_string(), "Flag".to_string()];
+    let cells = vec![
+      vec![MatrixCellValue::String("a_b c".to_string()), MatrixCellValue::Tag("5".to_string())],
+      vec![MatrixCellValue::Int(-5), MatrixCellValue::Unsigned(5), MatrixCellValue::Tag("é~".to_string())],
+      vec![MatrixCellValue::Bool(false), MatrixCellValue::Tag("".to_string())],
+    ];
+    for combination in generate_combinations(&cells) {
+      for param_names in [None, Some(names.as_slice())] {
+        let id = IdFormat::Reversible.format(&combination, param_names);
+        let parsed = IdFormat::Reversible.parse(&id, param_names, &cells).unwrap();
+        assert_eq!(parsed.cells, combination.cells, "round trip of '{}'", id);
+      }
+    }
+    assert!(decode_combination("Mode-+05", None).is_err());
+    assert!(decode_combination("Mode-~sa", Some(&["Kind".to_string()])).is_err());
+  }
+
+  #[test]
+  fn test_readable_parse_uses_axes() {
+    let names = vec!["Backend".to_string(), "BlockSize".to_string()];
+    let axes = vec![
+      vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Tag("Uring_Sq".to_string())],
+      vec![MatrixCellValue::Unsigned(512), MatrixCellValue::Unsigned(4096)],
+    ];
+    let parsed = IdFormat::Readable.parse("Backend-Uring_BlockSize-512", Some(&names), &axes).unwrap();
+    assert_eq!(parsed.cells, vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Unsigned(512)]);
+
+    let parsed = IdFormat::Readable.parse("_Uring_Sq_Uint4096", None, &axes).unwrap();
+    assert_eq!(parsed.cells, vec![MatrixCellValue::Tag("Uring_Sq".to_string()), MatrixCellValue::Unsigned(4096)]);
+
+    assert!(IdFormat::Readable.parse("Backend-Epoll_BlockSize-512", Some(&names), &axes).is_err());
+
+    let lossy = vec![vec![MatrixCellValue::Int(5), MatrixCellValue::Unsigned(5)]];
+    let err = IdFormat::Readable.parse("Offset-5", Some(&["Offset".to_string()]), &lossy).unwrap_err();
+    assert!(err.contains("ambiguous"), "unexpected error: {}", err);
+  }
+}
@@ -0,0 +1,230 @@
+//! Per-operation latency capture into an HDR-style histogram.
+//!
+//! Criterion measures whole iterations; a logic function that performs many operations per
+//! iteration (or cares about tail latency) records each operation's latency through a
+//! [`LatencyRecorder`] instead, and the suite reports the distribution per variant.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Values below this are counted exactly; above it, buckets keep 7 significant bits,
+/// i.e. a relative error below 1%.
+const SUB_BUCKETS: u64 = 128;
+const HALF_SUB_BUCKETS: u64 = SUB_BUCKETS / 2;
+const BUCKET_COUNT: usize = (SUB_BUCKETS + 57 * HALF_SUB_BUCKETS) as usize;
+
+/// A log-linear histogram of latencies in nanoseconds covering the whole `u64` range with a
+/// relative error below 1%. Count, min, max and mean are exact.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+  counts: Vec<u64>,
+  count: u64,
+  sum_nanos: u128,
+  min_nanos: u64,
+  max_nanos: u64,
+}
+
+impl Default for LatencyHistogram {
+  fn default() -> Self {
+    Self {
+      counts: vec![0; BUCKET_COUNT],
+      count: 0,
+      sum_nanos: 0,
+      min_nanos: u64::MAX,
+      max_nanos: 0,
+    }
+  }
+}
+
+impl LatencyHistogram {
+  /// Records one latency. Durations beyond `u64::MAX` nanoseconds are saturated.
+  pub fn record(&mut self, latency: Duration) {
+    self.record_nanos(u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX));
+  }
+
+  /// Records one latency given in nanoseconds.
+  pub fn record_nanos(&mut self, nanos: u64) {
+    self.counts[bucket_index(nanos)] += 1;
+    self.count += 1;
+    self.sum_nanos += u128::from(nanos);
+    self.min_nanos = self.min_nanos.min(nanos);
+    self.max_nanos = self.max_nanos.max(nanos);
+  }
+
+  /// Adds every latency recorded in `other`.
+  pub fn merge(&mut self, other: &LatencyHistogram) {
+    for (count, other_count) in self.counts.iter_mut().zip(&other.counts) {
+      *count += other_count;
+    }
+    self.count += other.count;
+    self.sum_nanos += other.sum_nanos;
+    self.min_nanos = self.min_nanos.min(other.min_nanos);
+    self.max_nanos = self.max_nanos.max(other.max_nanos);
+  }
+
+  /// Returns the number of recorded latencies.
+  pub fn count(&self) -> u64 {
+    self.count
+  }
+
+  /// Returns the latency at percentile `p` (0-100), or `None` if nothing was recorded.
+  pub fn percentile(&self, p: f64) -> Option<Duration> {
+    if self.count == 0 {
+      return None;
+    }
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * self.count as f64).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (index, &count) in self.counts.iter().enumerate() {
+      seen += count;
+      if seen >= rank {
+        let nanos = bucket_midpoint(index).clamp(self.min_nanos, self.max_nanos);
+        return Some(Duration::from_nanos(nanos));
+      }
+    }
+    Some(Duration::from_nanos(self.max_nanos))
+  }
+
+  /// Returns the aggregate of the recorded latencies, or `None` if nothing was recorded.
+  pub fn summarize(&self) -> Option<LatencySummary> {
+    if self.count == 0 {
+      return None;
+    }
+    let percentile = |p| self.percentile(p).unwrap_or_default();
+    Some(LatencySummary {
+      count: self.count,
+      min: Duration::from_nanos(self.min_nanos),
+      mean: Duration::from_nanos((self.sum_nanos / u128::from(self.count)) as u64),
+      p50: percentile(50.0),
+      p90: percentile(90.0),
+      p99: percentile(99.0),
+      p999: percentile(99.9),
+      max: Duration::from_nanos(self.max_nanos),
+    })
+  }
+}
+
+/// The latency distribution of one variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySummary {
+  /// The number of recorded operations.
+  pub count: u64,
+  pub min: Duration,
+  pub mean: Duration,
+  pub p50: Duration,
+  pub p90: Duration,
+  pub p99: Duration,
+  pub p999: Duration,
+  pub max: Duration,
+}
+
+/// Handle passed to recording logic functions to record per-operation latencies. Cloning it
+/// yields another handle to the same histogram, so it can be moved into spawned tasks.
+///
+/// # Example
+/// ```
+/// # use bench_matrix::LatencyRecorder;
+/// # use std::time::Duration;
+/// let recorder = LatencyRecorder::new();
+/// for micros in 1..=100 {
+///   recorder.record(Duration::from_micros(micros));
+/// }
+/// let summary = recorder.summarize().unwrap();
+/// assert_eq!(summary.max, Duration::from_micros(100));
+/// assert!(summary.p99 >= Duration::from_micros(98));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LatencyRecorder {
+  histogram: Arc<Mutex<LatencyHistogram>>,
+}
+
+impl LatencyRecorder {
+  /// Creates a recorder with an empty histogram.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Records the latency of one operation.
+  pub fn record(&self, latency: Duration) {
+    self.lock().record(latency);
+  }
+
+  /// Returns a copy of the histogram recorded so far.
+  pub fn histogram(&self) -> LatencyHistogram {
+    self.lock().clone()
+  }
+
+  /// Adds every latency recorded by `other` to this recorder.
+  #[cfg(feature = "criterion_integration")]
+  pub(crate) fn merge(&self, other: &LatencyRecorder) {
+    let other = other.histogram();
+    self.lock().merge(&other);
+  }
+
+  /// Returns the aggregate of the latencies recorded so far, or `None` if there are none.
+  pub fn summarize(&self) -> Option<LatencySummary> {
+    self.lock().summarize()
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, LatencyHistogram> {
+    // A panic while recording cannot leave the histogram inconsistent, so ignore poisoning.
+    self.histogram.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+  }
+}
+
+fn bucket_index(nanos: u64) -> usize {
+  if nanos < SUB_BUCKETS {
+    return nanos as usize;
+  }
+  let shift = (63 - nanos.leading_zeros() as u64) - 6;
+  (SUB_BUCKETS + (shift - 1) * HALF_SUB_BUCKETS + ((nanos >> shift) - HALF_SUB_BUCKETS)) as usize
+}
+
+fn bucket_midpoint(index: usize) -> u64 {
+  let index = index as u64;
+  if index < SUB_BUCKETS {
+    return index;
+  }
+  let offset = index - SUB_BUCKETS;
+  let shift = offset / HALF_SUB_BUCKETS + 1;
+  let lower = (offset % HALF_SUB_BUCKETS + HALF_SUB_BUCKETS) << shift;
+  lower + (1u64 << shift) / 2
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_buckets_cover_range_with_bounded_error() {
+    assert_eq!(bucket_index(127), 127);
+    assert_eq!(bucket_index(128), 128);
+    assert_eq!(bucket_index(u64::MAX), BUCKET_COUNT - 1);
+    for nanos in [128u64, 1_000, 65_537, 1_000_000_007, u64::MAX / 3] {
+      let midpoint = bucket_midpoint(bucket_index(nanos));
+      let error = (midpoint as f64 - nanos as f64).abs() / nanos as f64;
+      assert!(error < 0.01, "{} -> {} ({})", nanos, midpoint, error);
+    }
+  }
+
+  #[test]
+  fn test_percentiles_and_merge() {
+    let mut fast = LatencyHistogram::default();
+    let mut slow = LatencyHistogram::default();
+    for _ in 0..990 {
+      fast.record(Duration::from_micros(10));
+    }
+    for _ in 0..10 {
+      slow.record(Duration::from_millis(5));
+    }
+    fast.merge(&slow);
+
+    let summary = fast.summarize().unwrap();
+    assert_eq!(summary.count, 1000);
+    assert_eq!(summary.min, Duration::from_micros(10));
+    assert_eq!(summary.max, Duration::from_millis(5));
+    assert!(summary.p50.abs_diff(Duration::from_micros(10)) < Duration::from_nanos(100));
+    assert!(summary.p99.abs_diff(Duration::from_micros(10)) < Duration::from_nanos(100));
+    assert!(summary.p999.abs_diff(Duration::from_millis(5)) < Duration::from_micros(50));
+    assert!(LatencyHistogram::default().summarize().is_none());
+  }
+}
@@ -6,24 +6,81 @@
 // Define modules
 #[cfg(feature = "criterion_integration")]
 pub mod criterion_runner;
+#[cfg(feature = "json")]
+pub mod compare; // For ComparisonReport (comparing results against a baseline, regression gate)
+pub mod constraint; // For Constraint (combination exclusion rules)
+#[cfg(feature = "json")]
+pub mod export; // For ResultsTable (CSV/JSON export of Criterion's estimates)
+pub mod filter; // For MatrixFilter (narrowing axes from the environment / command line)
 pub mod generator; // For generate_combinations
+pub mod id; // For IdFormat (readable and reversible benchmark IDs)
+mod macros; // For the `bench_matrix!` declarative macro
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+pub mod matrix_file; // For loading axes and constraints from TOML/JSON/YAML files
+pub mod latency; // For LatencyRecorder (per-operation latency histograms)
+pub mod metrics; // For MetricsRecorder (user-reported per-iteration metrics)
 pub mod params; // For MatrixCellValue, AbstractCombination, etc. // For the Criterion-specific orchestrator
+pub mod pivot; // For PivotTable (Markdown and terminal pivot tables of results)
+pub mod plan; // For MatrixPlan (resolved variants, list mode)
+pub mod report; // For SuiteReport (bench_matrix's own per-variant results)
+#[cfg(feature = "standalone")]
+pub mod standalone; // For StandaloneSuite (running a matrix without Criterion)
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+pub mod test_mode; // For TestReport (running every combination once under `cargo test`)
+pub mod validate; // For ValidationError (up-front matrix validation)
 
 // Re-export key types for easier public use
+#[cfg(feature = "json")]
+pub use compare::{ComparisonReport, RegressionError, ResultSet, Verdict};
+pub use constraint::Constraint;
+#[cfg(feature = "json")]
+pub use export::{ExportError, ResultsTable};
+pub use filter::MatrixFilter;
 pub use generator::generate_combinations;
+pub use id::{IdFormat, IdTemplate};
+#[cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+pub use matrix_file::{MatrixDefinition, MatrixFileError};
+pub use latency::{LatencyHistogram, LatencyRecorder, LatencySummary};
+pub use metrics::{MetricSummary, MetricsRecorder};
 pub use params::{AbstractCombination, MatrixCellValue};
+pub use pivot::{PivotEntry, PivotResults, PivotTable, PivotValue};
+pub use plan::{MatrixPlan, PlannedVariant, SkipReason};
+pub use report::{OpenLoopSummary, SuiteReport, ThroughputReport, VariantReport};
+#[cfg(feature = "standalone")]
+pub use standalone::{SampleStats, StandaloneReport, StandaloneSuite, StandaloneVariantReport};
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+pub use test_mode::{CombinationTest, TestOutcome, TestReport};
+pub use validate::{ValidationError, ValidationProblem};
 
 // --- Re-exports for Criterion Integration (from the submodules) ---
 
+// Re-exported so that `bench_matrix!` expansions can reach Criterion's macros and types.
+#[cfg(feature = "criterion_integration")]
+pub use criterion;
+
 // Common types used by both async and sync criterion runners
 #[cfg(feature = "criterion_integration")]
-pub use criterion_runner::{ExtractorFn, GlobalSetupFn, GlobalTeardownFn};
+pub use criterion_runner::{
+  ConcurrencyFn, ConfigValidatorFn, ExtractorFn, GlobalSetupFn, GlobalTeardownFn, MetricsFn, RuntimeFactoryFn, StateThroughputFn,
+  VariantSettingsFn,
+};
+#[cfg(feature = "criterion_integration")]
+pub use criterion_runner::equivalence::{EquivalenceError, OutputMismatch};
+#[cfg(feature = "criterion_integration")]
+pub use criterion_runner::variant_settings::VariantSettings;
+#[cfg(feature = "criterion_integration")]
+pub use criterion_runner::executor::{BenchExecutor, BlockOnExecutor, BoxedFuture};
+#[cfg(all(feature = "criterion_integration", feature = "tokio"))]
+pub use criterion_runner::open_loop::{OpenLoop, OpenLoopRateFn, OpenLoopRequestFn};
 
 // Async specific exports
 #[cfg(feature = "criterion_integration")]
 pub use criterion_runner::async_suite::{
   AsyncBenchmarkLogicFn,
   AsyncBenchmarkSuite,
+  AsyncGlobalSetupFn,
+  AsyncGlobalTeardownFn,
+  AsyncRecordingLogicFn,
   // Function signature types for async benchmarks
   AsyncSetupFn,
   AsyncTeardownFn,
@@ -34,6 +91,7 @@ pub use criterion_runner::async_suite::{
 pub use criterion_runner::sync_suite::{
   SyncBenchmarkLogicFn,
   SyncBenchmarkSuite,
+  SyncRecordingLogicFn,
   // Function signature types for sync benchmarks
   SyncSetupFn,
   SyncTeardownFn,
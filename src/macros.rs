@@ -0,0 +1,198 @@
+#![cfg(feature = "criterion_integration")]
+
+/// Declares one or more benchmark suites together with the Criterion entry point.
+///
+/// The macro expands to:
+///
+/// * a `pub fn <name>(c: &mut Criterion)` that builds and runs every listed suite in order,
+/// * a `criterion_group!` named `benches` targeting that function (optionally with a custom
+///   `Criterion` configuration), and
+/// * a `criterion_main!(benches)` entry point.
+///
+/// Each suite is introduced by `sync "<SuiteName>"` or `async(<runtime>) "<SuiteName>"`, where
+/// `<runtime>` is an expression yielding the suite's executor, e.g. a `tokio::runtime::Runtime`.
+/// Axis values are converted with `MatrixCellValue::from`, so `"Tag"` becomes a `Tag`, `64u64`
+/// an `Unsigned` and `-1` an `Int`; any `MatrixCellValue` can also be written out explicitly.
+/// The optional `with` block lists builder calls applied to the suite before `run()`.
+///
+/// # Example
+/// ```no_run
+/// use bench_matrix::{bench_matrix, AbstractCombination, MatrixCellValue};
+/// use criterion::Throughput;
+/// use std::time::{Duration, Instant};
+///
+/// #[derive(Debug, Clone)]
+/// struct Config {
+///   elements: usize,
+/// }
+///
+/// fn extract(combo: &AbstractCombination) -> Result<Config, String> {
+///   Ok(Config { elements: combo.get_u64(1)? as usize })
+/// }
+///
+/// fn setup(cfg: &Config) -> Result<((), Vec<u64>), String> {
+///   Ok(((), (0..cfg.elements as u64).rev().collect()))
+/// }
+///
+/// fn logic(ctx: (), mut data: Vec<u64>, _cfg: &Config) -> ((), Vec<u64>, Duration) {
+///   let start = Instant::now();
+///   data.sort_unstable();
+///   (ctx, data, start.elapsed())
+/// }
+///
+/// fn teardown(_ctx: (), _data: Vec<u64>, _cfg: &Config) {}
+///
+/// bench_matrix! {
+///   name: sorting;
+///   sync "SortSuite" {
+///     config: Config,
+///     axes: {
+///       "Algo" => ["Unstable"],
+///       "Elements" => [100u64, 1_000u64],
+///     },
+///     extractor: extract,
+///     setup: setup,
+///     logic: logic,
+///     teardown: teardown,
+///     with: {
+///       throughput(|cfg: &Config| Throughput::Elements(cfg.elements as u64)),
+///     },
+///   }
+/// }
+/// ```
+///
+/// Suite kinds other than `sync` and `async(<runtime>)` are rejected at compile time:
+/// ```compile_fail
+/// # use bench_matrix::{bench_matrix, AbstractCombination};
+/// # use std::time::Duration;
+/// # fn extract(combo: &AbstractCombination) -> Result<u64, String> { combo.get_u64(0) }
+/// # fn setup(_cfg: &u64) -> Result<((), ()), String> { Ok(((), ())) }
+/// # fn logic(ctx: (), state: (), _cfg: &u64) -> ((), (), Duration) { (ctx, state, Duration::ZERO) }
+/// # fn teardown(_ctx: (), _state: (), _cfg: &u64) {}
+/// bench_matrix! {
+///   name: typo;
+///   snyc "Suite" {
+///     config: u64,
+///     axes: { "Size" => [1u64] },
+///     extractor: extract,
+///     setup: setup,
+///     logic: logic,
+///     teardown: teardown,
+///   }
+/// }
+/// ```
+#[macro_export]
+macro_rules! bench_matrix {
+  (
+    name: $name:ident;
+    $( config: $criterion_config:expr; )?
+    $(
+      $kind:tt $( ( $runtime:expr ) )? $suite_name:literal {
+        config: $cfg:ty,
+        axes: { $( $axis_name:literal => [ $( $value:expr ),* $(,)? ] ),+ $(,)? },
+        extractor: $extractor:expr,
+        setup: $setup:expr,
+        logic: $logic:expr,
+        teardown: $teardown:expr
+        $( , with: { $( $method:ident ( $( $arg:expr ),* $(,)? ) ),* $(,)? } )?
+        $(,)?
+      }
+    )+
+  ) => {
+    pub fn $name(c: &mut $crate::criterion::Criterion) {
+      $(
+        $crate::bench_matrix!(
+          @suite c, $kind [ $( $runtime )? ] $suite_name, $cfg,
+          [ $( $axis_name ),+ ],
+          [ $( [ $( $value ),* ] ),+ ],
+          $extractor, $setup, $logic, $teardown,
+          [ $( $( $method ( $( $arg ),* ) )* )? ]
+        );
+      )+
+    }
+
+    $crate::bench_matrix!(@entry $name $( , $criterion_config )?);
+  };
+
+  (@entry $name:ident) => {
+    $crate::criterion::criterion_group!(benches, $name);
+    $crate::criterion::criterion_main!(benches);
+  };
+
+  (@entry $name:ident, $criterion_config:expr) => {
+    $crate::criterion::criterion_group! {
+      name = benches;
+      config = $criterion_config;
+      targets = $name
+    }
+    $crate::criterion::criterion_main!(benches);
+  };
+
+  (
+    @suite $c:ident, sync [] $suite_name:literal, $cfg:ty,
+    [ $( $axis_name:literal ),+ ],
+    [ $( [ $( $value:expr ),* ] ),+ ],
+    $extractor:expr, $setup:expr, $logic:expr, $teardown:expr,
+    [ $( $method:ident ( $( $arg:expr ),* ) )* ]
+  ) => {{
+    let extractor: $crate::ExtractorFn<$cfg, _> = ::std::boxed::Box::new($extractor);
+    $crate::SyncBenchmarkSuite::new(
+      $c,
+      ::std::string::String::from($suite_name),
+      ::std::option::Option::Some(::std::vec![ $( ::std::string::String::from($axis_name) ),+ ]),
+      ::std::vec![ $( ::std::vec![ $( $crate::MatrixCellValue::from($value) ),* ] ),+ ],
+      extractor,
+      $setup,
+      $logic,
+      $teardown,
+    )
+    $( .$method( $( $arg ),* ) )*
+    .run();
+  }};
+
+  (
+    @suite $c:ident, async [ $runtime:expr ] $suite_name:literal, $cfg:ty,
+    [ $( $axis_name:literal ),+ ],
+    [ $( [ $( $value:expr ),* ] ),+ ],
+    $extractor:expr, $setup:expr, $logic:expr, $teardown:expr,
+    [ $( $method:ident ( $( $arg:expr ),* ) )* ]
+  ) => {{
+    let runtime = $runtime;
+    let extractor: $crate::ExtractorFn<$cfg, _> = ::std::boxed::Box::new($extractor);
+    $crate::AsyncBenchmarkSuite::new(
+      $c,
+      &runtime,
+      ::std::string::String::from($suite_name),
+      ::std::option::Option::Some(::std::vec![ $( ::std::string::String::from($axis_name) ),+ ]),
+      ::std::vec![ $( ::std::vec![ $( $crate::MatrixCellValue::from($value) ),* ] ),+ ],
+      extractor,
+      $setup,
+      $logic,
+      $teardown,
+    )
+    $( .$method( $( $arg ),* ) )*
+    .run();
+  }};
+
+  (@suite $c:ident, sync [ $runtime:expr ] $suite_name:literal, $( $rest:tt )*) => {
+    ::std::compile_error!(::std::concat!("suite ", ::std::stringify!($suite_name), ": `sync` suites take no runtime"));
+  };
+
+  (@suite $c:ident, async [] $suite_name:literal, $( $rest:tt )*) => {
+    ::std::compile_error!(::std::concat!(
+      "suite ",
+      ::std::stringify!($suite_name),
+      ": `async` suites need a runtime, e.g. `async(Runtime::new().unwrap())`"
+    ));
+  };
+
+  (@suite $c:ident, $kind:tt [ $( $runtime:expr )? ] $suite_name:literal, $( $rest:tt )*) => {
+    ::std::compile_error!(::std::concat!(
+      "suite ",
+      ::std::stringify!($suite_name),
+      ": unknown suite kind `",
+      ::std::stringify!($kind),
+      "`, expected `sync` or `async(<runtime>)`"
+    ));
+  };
+}
@@ -0,0 +1,450 @@
+#![cfg(any(feature = "toml", feature = "json", feature = "yaml"))]
+
+//! Loading parameter axes, axis names and exclusion constraints from a matrix file,
+//! so the values swept by a suite can change without recompiling the benchmark binary.
+//!
+//! The file format is the same for TOML, JSON and YAML (each behind its own feature):
+//!
+//! ```toml
+//! name = "IoSuite"            # optional
+//!
+//! [[axes]]
+//! name = "Backend"
+//! values = ["Uring", "Epoll"]
+//!
+//! [[axes]]
+//! name = "BlockSize"
+//! values = [512, 4096]
+//!
+//! [[exclude]]                 # optional, any number of rules
+//! Backend = "Epoll"
+//! BlockSize = [4096]
+//! ```
+//!
+//! Axis values follow the serde representation of `MatrixCellValue`: bare strings are `Tag`s,
+//! non-negative integers are `Unsigned`, negative integers are `Int`, booleans are `Bool`, and
+//! other variants are written as a single-key map such as `{ String = "Low" }` or `{ Int = 5 }`.
+//! Each `exclude` rule maps axis names to one value or a list of values; a combination
+//! matching every entry of a rule is excluded.
+
+use crate::constraint::Constraint;
+use crate::generator::generate_combinations;
+use crate::params::{AbstractCombination, MatrixCellValue};
+
+use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A parameter matrix loaded from a file: named axes plus exclusion constraints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixDefinition {
+  /// Optional suite name declared in the file.
+  pub name: Option<String>,
+  /// The axis names, in axis order.
+  pub parameter_names: Vec<String>,
+  /// The values of each axis, in axis order. Suitable for `generate_combinations`.
+  pub parameter_axes: Vec<Vec<MatrixCellValue>>,
+  /// Combinations matching any of these constraints should not be run.
+  pub exclusions: Vec<Constraint>,
+}
+
+impl MatrixDefinition {
+  /// Returns every combination of the axes that is not excluded by a constraint.
+  pub fn combinations(&self) -> impl Iterator<Item = AbstractCombination> + '_ {
+    generate_combinations(&self.parameter_axes).filter(move |combo| {
+      !self
+        .exclusions
+        .iter()
+        .any(|rule| rule.matches(combo, &self.parameter_names).unwrap_or(false))
+    })
+  }
+}
+
+/// Errors that can occur while loading a matrix file.
+#[derive(Debug)]
+pub enum MatrixFileError {
+  /// The file could not be read.
+  Io { path: PathBuf, source: std::io::Error },
+  /// The file extension does not map to an enabled format feature.
+  UnsupportedFormat { path: PathBuf },
+  /// The file is not syntactically valid for its format, or does not match the expected schema.
+  Parse { format: &'static str, message: String },
+  /// The file parsed but describes an invalid matrix (empty axes, unknown axis names, ...).
+  Invalid(String),
+}
+
+impl fmt::Display for MatrixFileError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      MatrixFileError::Io { path, source } => write!(f, "failed to read matrix file '{}': {}", path.display(), source),
+      MatrixFileError::UnsupportedFormat { path } => write!(
+        f,
+        "unsupported matrix file '{}': expected a .toml, .json, .yaml or .yml extension with the matching feature enabled",
+        path.display()
+      ),
+      MatrixFileError::Parse { format, message } => write!(f, "invalid {} matrix file: {}", format, message),
+      MatrixFileError::Invalid(message) => write!(f, "invalid matrix definition: {}", message),
+    }
+  }
+}
+
+impl std::error::Error for MatrixFileError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      MatrixFileError::Io { source, .. } => Some(source),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMatrixFile {
+  #[serde(default)]
+  name: Option<String>,
+  axes: Vec<RawAxis>,
+  #[serde(default)]
+  exclude: Vec<RawRule>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAxis {
+  name: String,
+  values: Vec<MatrixCellValue>,
+}
+
+/// One `exclude` rule: axis names mapped to one value or a list of values. Deserialized by hand
+/// so that a malformed entry is reported with its axis name and the expected shape.
+struct RawRule(BTreeMap<String, Vec<MatrixCellValue>>);
+
+impl<'de> Deserialize<'de> for RawRule {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct RuleVisitor;
+
+    impl<'de> Visitor<'de> for RuleVisitor {
+      type Value = RawRule;
+
+      fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an exclude rule mapping axis names to one value or a list of values")
+      }
+
+      fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut rule = BTreeMap::new();
+        while let Some(axis) = map.next_key::<String>()? {
+          let values = map.next_value_seed(RuleValues { axis: &axis })?;
+          rule.insert(axis, values);
+        }
+        Ok(RawRule(rule))
+      }
+    }
+
+    deserializer.deserialize_map(RuleVisitor)
+  }
+}
+
+/// The values of one exclude rule entry: a single `MatrixCellValue` or a list of them.
+struct RuleValues<'a> {
+  axis: &'a str,
+}
+
+impl RuleValues<'_> {
+  fn in_entry<E: serde::de::Error>(&self, err: E) -> E {
+    E::custom(format!("exclude entry '{}': {}", self.axis, err))
+  }
+}
+
+impl<'de> DeserializeSeed<'de> for RuleValues<'_> {
+  type Value = Vec<MatrixCellValue>;
+
+  fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+    deserializer.deserialize_any(self)
+  }
+}
+
+impl<'de> Visitor<'de> for RuleValues<'_> {
+  type Value = Vec<MatrixCellValue>;
+
+  fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "one value or a list of values for axis '{}'", self.axis)
+  }
+
+  fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+    MatrixCellValue::deserialize(v.into_deserializer()).map(|value| vec![value])
+  }
+
+  fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+    MatrixCellValue::deserialize(v.into_deserializer()).map(|value| vec![value])
+  }
+
+  fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+    MatrixCellValue::deserialize(v.into_deserializer()).map(|value| vec![value])
+  }
+
+  fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+    MatrixCellValue::deserialize(v.into_deserializer()).map(|value| vec![value])
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+    MatrixCellValue::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+      .map(|value| vec![value])
+      .map_err(|err| self.in_entry(err))
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    let mut values = Vec::new();
+    while let Some(value) = seq.next_element::<MatrixCellValue>().map_err(|err| self.in_entry(err))? {
+      values.push(value);
+    }
+    Ok(values)
+  }
+}
+
+impl RawMatrixFile {
+  fn into_definition(self) -> Result<MatrixDefinition, MatrixFileError> {
+    if self.axes.is_empty() {
+      return Err(MatrixFileError::Invalid("no axes defined".to_string()));
+    }
+
+    let mut parameter_names: Vec<String> = Vec::with_capacity(self.axes.len());
+    let mut parameter_axes = Vec::with_capacity(self.axes.len());
+    for (index, axis) in self.axes.into_iter().enumerate() {
+      if axis.name.trim().is_empty() {
+        return Err(MatrixFileError::Invalid(format!("axis #{} has an empty name", index + 1)));
+      }
+      if parameter_names.contains(&axis.name) {
+        return Err(MatrixFileError::Invalid(format!("axis '{}' is defined more than once", axis.name)));
+      }
+      if axis.values.is_empty() {
+        return Err(MatrixFileError::Invalid(format!("axis '{}' has no values", axis.name)));
+      }
+      for (value_index, value) in axis.values.iter().enumerate() {
+        if axis.values[..value_index].contains(value) {
+          return Err(MatrixFileError::Invalid(format!(
+            "axis '{}' lists the value {:?} more than once",
+            axis.name, value
+          )));
+        }
+      }
+      parameter_names.push(axis.name);
+      parameter_axes.push(axis.values);
+    }
+
+    let mut exclusions = Vec::with_capacity(self.exclude.len());
+    for (rule_index, rule) in self.exclude.into_iter().enumerate() {
+      if rule.0.is_empty() {
+        return Err(MatrixFileError::Invalid(format!(
+          "exclude rule #{} is empty and would exclude every combination",
+          rule_index + 1
+        )));
+      }
+      let mut constraint = Constraint::new();
+      for (axis, values) in rule.0 {
+        let Some(axis_index) = parameter_names.iter().position(|name| *name == axis) else {
+          return Err(MatrixFileError::Invalid(format!(
+            "exclude rule #{} references unknown axis '{}' (known axes: {})",
+            rule_index + 1,
+            axis,
+            parameter_names.join(", ")
+          )));
+        };
+        if let Some(unknown) = values.iter().find(|value| !parameter_axes[axis_index].contains(value)) {
+          return Err(MatrixFileError::Invalid(format!(
+            "exclude rule #{} uses value {:?} which is not on axis '{}' (axis values: {:?})",
+            rule_index + 1,
+            unknown,
+            axis,
+            parameter_axes[axis_index]
+          )));
+        }
+        constraint = constraint.when(axis, values);
+      }
+      exclusions.push(constraint);
+    }
+
+    Ok(MatrixDefinition {
+      name: self.name,
+      parameter_names,
+      parameter_axes,
+      exclusions,
+    })
+  }
+}
+
+/// Parses a matrix definition from a TOML document.
+#[cfg(feature = "toml")]
+pub fn from_toml_str(input: &str) -> Result<MatrixDefinition, MatrixFileError> {
+  let raw: RawMatrixFile = toml::from_str(input).map_err(|e| MatrixFileError::Parse {
+    format: "TOML",
+    message: e.to_string(),
+  })?;
+  raw.into_definition()
+}
+
+/// Parses a matrix definition from a JSON document.
+#[cfg(feature = "json")]
+pub fn from_json_str(input: &str) -> Result<MatrixDefinition, MatrixFileError> {
+  let raw: RawMatrixFile = serde_json::from_str(input).map_err(|e| MatrixFileError::Parse {
+    format: "JSON",
+    message: e.to_string(),
+  })?;
+  raw.into_definition()
+}
+
+/// Parses a matrix definition from a YAML document.
+#[cfg(feature = "yaml")]
+pub fn from_yaml_str(input: &str) -> Result<MatrixDefinition, MatrixFileError> {
+  let raw: RawMatrixFile = serde_yaml::from_str(input).map_err(|e| MatrixFileError::Parse {
+    format: "YAML",
+    message: e.to_string(),
+  })?;
+  raw.into_definition()
+}
+
+/// Loads a matrix definition from a file, choosing the format from its extension
+/// (`.toml`, `.json`, `.yaml`/`.yml`). The corresponding format feature must be enabled.
+pub fn load(path: impl AsRef<Path>) -> Result<MatrixDefinition, MatrixFileError> {
+  let path = path.as_ref();
+  let extension = path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .map(str::to_ascii_lowercase);
+
+  let parse: fn(&str) -> Result<MatrixDefinition, MatrixFileError> = match extension.as_deref() {
+    #[cfg(feature = "toml")]
+    Some("toml") => from_toml_str,
+    #[cfg(feature = "json")]
+    Some("json") => from_json_str,
+    #[cfg(feature = "yaml")]
+    Some("yaml") | Some("yml") => from_yaml_str,
+    _ => {
+      return Err(MatrixFileError::UnsupportedFormat {
+        path: path.to_path_buf(),
+      });
+    }
+  };
+
+  let contents = std::fs::read_to_string(path).map_err(|source| MatrixFileError::Io {
+    path: path.to_path_buf(),
+    source,
+  })?;
+
+  parse(&contents).map_err(|err| match err {
+    MatrixFileError::Parse { format, message } => MatrixFileError::Parse {
+      format,
+      message: format!("{}: {}", path.display(), message),
+    },
+    MatrixFileError::Invalid(message) => MatrixFileError::Invalid(format!("{}: {}", path.display(), message)),
+    other => other,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[cfg(feature = "toml")]
+  #[test]
+  fn test_from_toml_str_axes_and_exclusions() {
+    let def = from_toml_str(
+      r#"
+        name = "IoSuite"
+
+        [[axes]]
+        name = "Backend"
+        values = ["Uring", "Epoll"]
+
+        [[axes]]
+        name = "BlockSize"
+        values = [512, 4096]
+
+        [[axes]]
+        name = "Mode"
+        values = [{ String = "Low" }, { Int = 5 }, -1, true]
+
+        [[exclude]]
+        Backend = "Epoll"
+        BlockSize = [4096]
+      "#,
+    )
+    .unwrap();
+
+    assert_eq!(def.name.as_deref(), Some("IoSuite"));
+    assert_eq!(def.parameter_names, vec!["Backend", "BlockSize", "Mode"]);
+    assert_eq!(
+      def.parameter_axes[2],
+      vec![
+        MatrixCellValue::String("Low".to_string()),
+        MatrixCellValue::Int(5),
+        MatrixCellValue::Int(-1),
+        MatrixCellValue::Bool(true),
+      ]
+    );
+    assert_eq!(def.exclusions.len(), 1);
+    // 2 * 2 * 4 combinations, minus the 4 (Epoll, 4096, *) ones.
+    assert_eq!(def.combinations().count(), 12);
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn test_from_json_str_reports_unknown_axis() {
+    let err = from_json_str(
+      r#"{ "axes": [ { "name": "Backend", "values": ["Uring"] } ], "exclude": [ { "Backnd": "Uring" } ] }"#,
+    )
+    .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("unknown axis 'Backnd'"), "unexpected error: {}", message);
+
+    let err = from_json_str(r#"{ "axes": [ { "name": "Backend", "values": [] } ] }"#).unwrap_err();
+    assert!(err.to_string().contains("axis 'Backend' has no values"));
+  }
+
+  #[cfg(feature = "json")]
+  #[test]
+  fn test_from_json_str_names_the_malformed_exclude_entry() {
+    let axes = r#""axes": [ { "name": "Backend", "values": ["Uring"] }, { "name": "Depth", "values": [1] } ]"#;
+
+    let err = from_json_str(&format!(r#"{{ {}, "exclude": [ {{ "Depth": 1.5 }} ] }}"#, axes)).unwrap_err();
+    let message = err.to_string();
+    assert!(
+      message.contains("invalid type: floating point `1.5`, expected one value or a list of values for axis 'Depth'"),
+      "unexpected error: {}",
+      message
+    );
+
+    let err = from_json_str(&format!(r#"{{ {}, "exclude": [ {{ "Backend": [{{ "Float": 1 }}] }} ] }}"#, axes)).unwrap_err();
+    let message = err.to_string();
+    assert!(
+      message.contains("exclude entry 'Backend': unknown variant `Float`"),
+      "unexpected error: {}",
+      message
+    );
+  }
+
+  #[cfg(feature = "yaml")]
+  #[test]
+  fn test_from_yaml_str_round_trips_cell_values() {
+    let cells = vec![
+      MatrixCellValue::Tag("Uring".to_string()),
+      MatrixCellValue::String("a b".to_string()),
+      MatrixCellValue::Int(7),
+      MatrixCellValue::Int(-7),
+      MatrixCellValue::Unsigned(7),
+      MatrixCellValue::Bool(false),
+    ];
+    let yaml = serde_yaml::to_string(&cells).unwrap();
+    let parsed: Vec<MatrixCellValue> = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(parsed, cells);
+
+    let err = from_yaml_str("axes:\n  - name: Backend\n    valuez: [Uring]\n").unwrap_err();
+    assert!(matches!(err, MatrixFileError::Parse { format: "YAML", .. }));
+  }
+
+  #[test]
+  fn test_load_rejects_unknown_extension() {
+    let err = load("matrix.ini").unwrap_err();
+    assert!(matches!(err, MatrixFileError::UnsupportedFormat { .. }));
+  }
+}
@@ -0,0 +1,143 @@
+//! User-reported domain metrics (cache hit rate, retries, queue depth, ...) recorded per
+//! iteration and aggregated per variant.
+
+/// Number of values kept per metric for percentile estimation. Count, mean, min and max are
+/// always exact; percentiles come from a uniform sample of at most this many values.
+const RESERVOIR_SIZE: usize = 10_000;
+
+/// Handle passed to a suite's `collect_metrics` hook to record named values.
+///
+/// # Example
+/// ```
+/// # use bench_matrix::MetricsRecorder;
+/// let mut recorder = MetricsRecorder::default();
+/// for retries in [0.0, 2.0, 1.0] {
+///   recorder.record("retries", retries);
+/// }
+/// let summary = &recorder.summarize()[0];
+/// assert_eq!((summary.count, summary.mean, summary.max), (3, 1.0, 2.0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MetricsRecorder {
+  series: Vec<MetricSeries>,
+}
+
+#[derive(Debug, Clone)]
+struct MetricSeries {
+  name: String,
+  count: u64,
+  sum: f64,
+  min: f64,
+  max: f64,
+  reservoir: Vec<f64>,
+  rng_state: u64,
+}
+
+impl MetricsRecorder {
+  /// Records one value of the metric `name`. Non-finite values are ignored.
+  pub fn record(&mut self, name: &str, value: f64) {
+    if !value.is_finite() {
+      return;
+    }
+    let index = match self.series.iter().position(|series| series.name == name) {
+      Some(index) => index,
+      None => {
+        self.series.push(MetricSeries {
+          name: name.to_string(),
+          count: 0,
+          sum: 0.0,
+          min: f64::INFINITY,
+          max: f64::NEG_INFINITY,
+          reservoir: Vec::new(),
+          rng_state: 0x9E37_79B9_7F4A_7C15,
+        });
+        self.series.len() - 1
+      }
+    };
+    self.series[index].push(value);
+  }
+
+  /// Returns the aggregate of every metric, in order of first recording.
+  pub fn summarize(&self) -> Vec<MetricSummary> {
+    self.series.iter().map(MetricSeries::summarize).collect()
+  }
+}
+
+impl MetricSeries {
+  fn push(&mut self, value: f64) {
+    self.count += 1;
+    self.sum += value;
+    self.min = self.min.min(value);
+    self.max = self.max.max(value);
+    if self.reservoir.len() < RESERVOIR_SIZE {
+      self.reservoir.push(value);
+    } else {
+      // Reservoir sampling (algorithm R) with a xorshift generator, so results are reproducible.
+      self.rng_state ^= self.rng_state << 13;
+      self.rng_state ^= self.rng_state >> 7;
+      self.rng_state ^= self.rng_state << 17;
+      let slot = (self.rng_state % self.count) as usize;
+      if slot < RESERVOIR_SIZE {
+        self.reservoir[slot] = value;
+      }
+    }
+  }
+
+  fn summarize(&self) -> MetricSummary {
+    let mut sorted = self.reservoir.clone();
+    sorted.sort_by(f64::total_cmp);
+    let percentile = |p: f64| {
+      let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+      sorted[rank.clamp(1, sorted.len()) - 1]
+    };
+    MetricSummary {
+      name: self.name.clone(),
+      count: self.count,
+      mean: self.sum / self.count as f64,
+      min: self.min,
+      max: self.max,
+      p50: percentile(50.0),
+      p90: percentile(90.0),
+      p99: percentile(99.0),
+    }
+  }
+}
+
+/// The aggregate of one metric over every iteration of a variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSummary {
+  pub name: String,
+  /// The number of recorded values.
+  pub count: u64,
+  pub mean: f64,
+  pub min: f64,
+  pub max: f64,
+  pub p50: f64,
+  pub p90: f64,
+  pub p99: f64,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_summary_is_exact_beyond_reservoir() {
+    let mut recorder = MetricsRecorder::default();
+    for value in 1..=(RESERVOIR_SIZE as u64 * 3) {
+      recorder.record("queue_depth", value as f64);
+    }
+    recorder.record("hit_rate", 0.5);
+    recorder.record("hit_rate", f64::NAN);
+
+    let summaries = recorder.summarize();
+    assert_eq!(summaries.len(), 2);
+    let depth = &summaries[0];
+    assert_eq!(depth.count, RESERVOIR_SIZE as u64 * 3);
+    assert_eq!((depth.min, depth.max), (1.0, RESERVOIR_SIZE as f64 * 3.0));
+    assert_eq!(depth.mean, (RESERVOIR_SIZE as f64 * 3.0 + 1.0) / 2.0);
+    let expected_p50 = RESERVOIR_SIZE as f64 * 1.5;
+    assert!((depth.p50 - expected_p50).abs() < expected_p50 * 0.05, "p50 {}", depth.p50);
+    assert_eq!(summaries[1].count, 1);
+  }
+}
@@ -89,6 +89,102 @@ impl From<bool> for MatrixCellValue {
   }
 }
 
+// --- Optional: serde support for MatrixCellValue ---
+// Values use the most natural representation for hand-written matrix files:
+// bare strings are `Tag`s, non-negative integers are `Unsigned`, negative integers
+// are `Int`, and booleans are `Bool`. Anything that would not round-trip through
+// that shorthand (a `String`, or a non-negative `Int`) is written as a single-key
+// map naming the variant, e.g. `{ "String": "Low" }` or `{ "Int": 5 }`.
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MatrixCellValue {
+  fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+    use serde::ser::SerializeMap;
+    match self {
+      MatrixCellValue::Tag(s) => serializer.serialize_str(s),
+      MatrixCellValue::String(s) => {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("String", s)?;
+        map.end()
+      }
+      MatrixCellValue::Int(i) if *i < 0 => serializer.serialize_i64(*i),
+      MatrixCellValue::Int(i) => {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("Int", i)?;
+        map.end()
+      }
+      MatrixCellValue::Unsigned(u) => serializer.serialize_u64(*u),
+      MatrixCellValue::Bool(b) => serializer.serialize_bool(*b),
+    }
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MatrixCellValue {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct CellVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for CellVisitor {
+      type Value = MatrixCellValue;
+
+      fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+          "a string (Tag), an integer, a boolean, or a single-key map such as { String = \"..\" } or { Int = 5 }",
+        )
+      }
+
+      fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(MatrixCellValue::Bool(v))
+      }
+
+      fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        if v < 0 {
+          Ok(MatrixCellValue::Int(v))
+        } else {
+          Ok(MatrixCellValue::Unsigned(v as u64))
+        }
+      }
+
+      fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(MatrixCellValue::Unsigned(v))
+      }
+
+      fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(MatrixCellValue::Tag(v.to_string()))
+      }
+
+      fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        use serde::de::Error;
+        let key: String = map
+          .next_key()?
+          .ok_or_else(|| A::Error::custom("expected a single-key map naming the value type, found an empty map"))?;
+        let value = match key.as_str() {
+          "Tag" => MatrixCellValue::Tag(map.next_value()?),
+          "String" => MatrixCellValue::String(map.next_value()?),
+          "Int" => MatrixCellValue::Int(map.next_value()?),
+          "Unsigned" => MatrixCellValue::Unsigned(map.next_value()?),
+          "Bool" => MatrixCellValue::Bool(map.next_value()?),
+          other => {
+            return Err(A::Error::unknown_variant(
+              other,
+              &["Tag", "String", "Int", "Unsigned", "Bool"],
+            ));
+          }
+        };
+        if let Some(extra) = map.next_key::<String>()? {
+          return Err(A::Error::custom(format!(
+            "expected a single-key map naming the value type, found an extra key '{}'",
+            extra
+          )));
+        }
+        Ok(value)
+      }
+    }
+
+    deserializer.deserialize_any(CellVisitor)
+  }
+}
+
 /// Represents one specific combination of abstract parameter values,
 /// forming a "row" in the conceptual table of all configurations to benchmark.
 /// The order of `MatrixCellValue`s in the `cells` vector corresponds to the
@@ -100,6 +196,14 @@ pub struct AbstractCombination {
 }
 
 impl AbstractCombination {
+  /// Returns the combination made of the cells at `indices`, in that order.
+  /// Panics if an index is out of bounds.
+  pub fn select(&self, indices: &[usize]) -> AbstractCombination {
+    AbstractCombination {
+      cells: indices.iter().map(|&index| self.cells[index].clone()).collect(),
+    }
+  }
+
   /// Generates a string suffix suitable for use in benchmark IDs,
   /// created by joining the string representations of its cell values.
   /// Example: "_Tag(StdTokio)_HWM(Low)_MsgSize(64)"
@@ -0,0 +1,653 @@
+//! Pivot tables of a suite's results, for pasting into PR descriptions or reading in a terminal.
+//!
+//! A `PivotTable` puts the values of one axis down the side and another across the top, with
+//! the mean time or a throughput of each variant in the cells. The remaining axes are either
+//! fixed to one value or split the table into one section per combination of their values.
+//! The fastest cell of each row is highlighted: bold in Markdown, bold green (or marked with
+//! `*` when color is off) in the terminal. With `relative_to`, cells show each variant's value
+//! as a ratio to a reference variant instead, e.g. `0.62x` the time of `Impl=Std`.
+
+use crate::filter::plain_text;
+use crate::id::format_duration;
+use crate::params::{AbstractCombination, MatrixCellValue};
+use crate::report::{format_rate, SuiteReport, ThroughputReport};
+
+use std::fmt::Write as _;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// What the cells of a pivot table show.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PivotValue {
+  /// The mean time per iteration; lower is faster.
+  #[default]
+  Time,
+  /// The rate of the throughput declared under this name; higher is faster.
+  Throughput(String),
+}
+
+/// The results of one variant, as pivot tables read them.
+#[derive(Debug, Clone)]
+pub struct PivotEntry {
+  pub combination: AbstractCombination,
+  pub mean_time: Option<Duration>,
+  pub throughputs: Vec<ThroughputReport>,
+}
+
+/// A suite's results, as pivot tables read them. Built from a `SuiteReport`, a
+/// `StandaloneReport` or a `ResultsTable`.
+#[derive(Debug, Clone, Default)]
+pub struct PivotResults {
+  pub suite_name: String,
+  pub parameter_names: Option<Vec<String>>,
+  pub entries: Vec<PivotEntry>,
+}
+
+impl From<&SuiteReport> for PivotResults {
+  fn from(report: &SuiteReport) -> Self {
+    Self {
+      suite_name: report.suite_name.clone(),
+      parameter_names: report.parameter_names.clone(),
+      entries: report
+        .variants
+        .iter()
+        .map(|variant| PivotEntry {
+          combination: variant.combination.clone(),
+          mean_time: variant.run_mean_time(),
+          throughputs: variant.throughputs.clone(),
+        })
+        .collect(),
+    }
+  }
+}
+
+#[cfg(feature = "standalone")]
+impl From<&crate::standalone::StandaloneReport> for PivotResults {
+  fn from(report: &crate::standalone::StandaloneReport) -> Self {
+    Self {
+      suite_name: report.suite_name.clone(),
+      parameter_names: report.parameter_names.clone(),
+      entries: report
+        .variants
+        .iter()
+        .map(|variant| PivotEntry {
+          combination: variant.combination.clone(),
+          mean_time: Some(variant.stats.mean),
+          throughputs: Vec::new(),
+        })
+        .collect(),
+    }
+  }
+}
+
+impl PivotResults {
+  /// Returns each entry's `value` as a ratio to its reference variant: the entry holding
+  /// `reference` on `axis` and the same values as it on every other axis. Ratios are in entry
+  /// order, `None` where the entry or its reference has no value.
+  pub fn relative_to(
+    &self,
+    axis: &str,
+    reference: impl Into<MatrixCellValue>,
+    value: &PivotValue,
+  ) -> Result<Vec<Option<f64>>, String> {
+    let reference = reference.into();
+    let names = self
+      .parameter_names
+      .as_deref()
+      .ok_or_else(|| "Relative values require parameter names".to_string())?;
+    let axis_index = names
+      .iter()
+      .position(|name| name == axis)
+      .ok_or_else(|| format!("Unknown axis '{}' (known axes: {})", axis, names.join(", ")))?;
+    let references: Vec<&PivotEntry> = self
+      .entries
+      .iter()
+      .filter(|entry| entry.combination.cells.get(axis_index) == Some(&reference))
+      .collect();
+    if references.is_empty() {
+      return Err(format!("No variant has {}={}", axis, plain_text(&reference)));
+    }
+
+    let others = |entry: &PivotEntry| {
+      let mut cells = entry.combination.cells.clone();
+      cells.remove(axis_index);
+      cells
+    };
+    Ok(
+      self
+        .entries
+        .iter()
+        .map(|entry| {
+          if entry.combination.cells.len() != names.len() {
+            return None;
+          }
+          let key = others(entry);
+          let reference_entry = references.iter().find(|candidate| others(candidate) == key)?;
+          let reference_value = reference_entry.value(value).filter(|reference_value| *reference_value > 0.0)?;
+          Some(entry.value(value)? / reference_value)
+        })
+        .collect(),
+    )
+  }
+}
+
+impl PivotEntry {
+  /// Returns the entry's `value`: seconds for the time, units per second for a throughput.
+  fn value(&self, value: &PivotValue) -> Option<f64> {
+    match value {
+      PivotValue::Time => self.mean_time.map(|mean| mean.as_secs_f64()),
+      PivotValue::Throughput(name) => self
+        .throughputs
+        .iter()
+        .find(|throughput| throughput.name == *name)
+        .map(|throughput| throughput.per_second),
+    }
+  }
+}
+
+/// Uses Criterion's estimates where available, so times and rates match the exported files.
+#[cfg(feature = "json")]
+impl From<&crate::export::ResultsTable> for PivotResults {
+  fn from(table: &crate::export::ResultsTable) -> Self {
+    Self {
+      suite_name: table.suite_name.clone(),
+      parameter_names: Some(table.parameter_names.clone()),
+      entries: table
+        .rows
+        .iter()
+        .map(|row| PivotEntry {
+          combination: row.combination.clone(),
+          mean_time: row.mean_ns().map(|ns| Duration::from_secs_f64(ns / 1e9)),
+          throughputs: row
+            .report
+            .throughputs
+            .iter()
+            .map(|throughput| ThroughputReport {
+              per_second: row.per_second(throughput),
+              ..throughput.clone()
+            })
+            .collect(),
+        })
+        .collect(),
+    }
+  }
+}
+
+/// A pivot table layout: which axes form the rows and columns, what the cells show and which
+/// of the other axes are fixed.
+#[derive(Debug, Clone)]
+pub struct PivotTable {
+  rows: String,
+  columns: String,
+  value: PivotValue,
+  fixed: Vec<(String, MatrixCellValue)>,
+  reference: Option<(String, MatrixCellValue)>,
+  color: Option<bool>,
+}
+
+/// One table of a rendered pivot: the values of the sectioning axes it covers, and its cells.
+struct Section {
+  title: Option<String>,
+  key: Vec<MatrixCellValue>,
+  row_values: Vec<MatrixCellValue>,
+  cells: Vec<Vec<Option<f64>>>,
+}
+
+/// A pivot resolved against a suite's results.
+struct Pivoted {
+  heading: String,
+  row_axis: String,
+  column_axis: String,
+  column_values: Vec<MatrixCellValue>,
+  sections: Vec<Section>,
+  format: CellFormat,
+}
+
+/// How the values of a pivot's cells are rendered.
+enum CellFormat {
+  Time,
+  /// A rate in the given unit, e.g. `bytes/s`.
+  Rate(String),
+  Ratio,
+}
+
+impl PivotTable {
+  /// Creates a layout with the values of `rows` down the side and of `columns` across the
+  /// top, showing the mean time per iteration.
+  pub fn new(rows: impl Into<String>, columns: impl Into<String>) -> Self {
+    Self {
+      rows: rows.into(),
+      columns: columns.into(),
+      value: PivotValue::Time,
+      fixed: Vec::new(),
+      reference: None,
+      color: None,
+    }
+  }
+
+  /// Sets what the cells show.
+  pub fn value(mut self, value: PivotValue) -> Self {
+    self.value = value;
+    self
+  }
+
+  /// Only includes variants whose `axis` holds `value`. Axes that are neither pivoted nor
+  /// fixed split the table into sections.
+  pub fn fix(mut self, axis: impl Into<String>, value: impl Into<MatrixCellValue>) -> Self {
+    self.fixed.push((axis.into(), value.into()));
+    self
+  }
+
+  /// Shows each variant's value as a ratio to the variant holding `value` on `axis` and the
+  /// same values on every other axis, e.g. `0.62x` for a variant taking 62% of the reference's
+  /// time. Ratios are shown to two significant digits, e.g. `2.3x` or `15x`. Cells without a
+  /// matching reference are empty.
+  pub fn relative_to(mut self, axis: impl Into<String>, value: impl Into<MatrixCellValue>) -> Self {
+    self.reference = Some((axis.into(), value.into()));
+    self
+  }
+
+  /// Forces ANSI colors in terminal tables on or off. By default they are used when stdout is
+  /// a terminal and `NO_COLOR` is not set.
+  pub fn color(mut self, enabled: bool) -> Self {
+    self.color = Some(enabled);
+    self
+  }
+
+  /// Renders the table as GitHub-flavored Markdown, with the fastest cell of each row in bold.
+  pub fn to_markdown(&self, results: impl Into<PivotResults>) -> Result<String, String> {
+    let pivoted = self.pivot(results.into())?;
+    let mut out = String::new();
+    let _ = writeln!(out, "**{}**", pivoted.heading);
+    for section in &pivoted.sections {
+      out.push('\n');
+      if let Some(title) = &section.title {
+        let _ = writeln!(out, "*{}*\n", title);
+      }
+      let _ = write!(out, "| {} \\ {} |", pivoted.row_axis, pivoted.column_axis);
+      for value in &pivoted.column_values {
+        let _ = write!(out, " {} |", plain_text(value));
+      }
+      out.push_str("\n|:---|");
+      out.push_str(&"---:|".repeat(pivoted.column_values.len()));
+      out.push('\n');
+      for (row_value, row) in section.row_values.iter().zip(&section.cells) {
+        let best = self.best(row);
+        let _ = write!(out, "| {} |", plain_text(row_value));
+        for cell in row {
+          let text = pivoted.format(*cell);
+          if cell.is_some() && *cell == best {
+            let _ = write!(out, " **{}** |", text);
+          } else {
+            let _ = write!(out, " {} |", text);
+          }
+        }
+        out.push('\n');
+      }
+    }
+    Ok(out)
+  }
+
+  /// Renders the table as aligned plain text for a terminal, highlighting the fastest cell of
+  /// each row.
+  pub fn to_terminal(&self, results: impl Into<PivotResults>) -> Result<String, String> {
+    let pivoted = self.pivot(results.into())?;
+    let color = self
+      .color
+      .unwrap_or_else(|| std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none());
+    let corner = format!("{} \\ {}", pivoted.row_axis, pivoted.column_axis);
+    let headers: Vec<String> = pivoted.column_values.iter().map(plain_text).collect();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", pivoted.heading);
+    for section in &pivoted.sections {
+      let rows: Vec<(String, Vec<(String, bool)>)> = section
+        .row_values
+        .iter()
+        .zip(&section.cells)
+        .map(|(row_value, row)| {
+          let best = self.best(row);
+          let cells = row
+            .iter()
+            .map(|cell| (pivoted.format(*cell), cell.is_some() && *cell == best))
+            .collect();
+          (plain_text(row_value), cells)
+        })
+        .collect();
+
+      let label_width = rows
+        .iter()
+        .map(|(label, _)| width(label))
+        .chain([width(&corner)])
+        .max()
+        .unwrap_or(0);
+      // Without color, the fastest cell is marked with a trailing `*`; every cell reserves room
+      // for it so the columns stay aligned.
+      let marker = if color { 0 } else { 1 };
+      let column_widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+          rows
+            .iter()
+            .map(|(_, cells)| width(&cells[index].0) + marker)
+            .chain([width(header)])
+            .max()
+            .unwrap_or(0)
+        })
+        .collect();
+
+      out.push('\n');
+      if let Some(title) = &section.title {
+        let _ = writeln!(out, "{}", title);
+      }
+      let _ = write!(out, "  {}", pad_right(&corner, label_width));
+      for (header, column_width) in headers.iter().zip(&column_widths) {
+        let _ = write!(out, "  {}", pad_left(header, *column_width));
+      }
+      out.push('\n');
+      for (label, cells) in &rows {
+        let _ = write!(out, "  {}", pad_right(label, label_width));
+        for ((text, fastest), column_width) in cells.iter().zip(&column_widths) {
+          let text = match (*fastest, color) {
+            (true, true) => format!("{}\x1b[1;32m{}\x1b[0m", " ".repeat(column_width - width(text)), text),
+            (true, false) => pad_left(&format!("{}*", text), *column_width),
+            (false, _) => pad_left(&format!("{}{}", text, " ".repeat(marker)), *column_width),
+          };
+          let _ = write!(out, "  {}", text);
+        }
+        out.push('\n');
+      }
+    }
+    if !color {
+      let _ = writeln!(out, "\n* fastest in row");
+    }
+    Ok(out)
+  }
+
+  /// Returns the fastest value of a row, if it has at least two values to compare.
+  fn best(&self, row: &[Option<f64>]) -> Option<f64> {
+    let values = row.iter().flatten().copied();
+    if values.clone().count() < 2 {
+      return None;
+    }
+    match self.value {
+      PivotValue::Time => values.reduce(f64::min),
+      PivotValue::Throughput(_) => values.reduce(f64::max),
+    }
+  }
+
+  fn pivot(&self, results: PivotResults) -> Result<Pivoted, String> {
+    let names = results
+      .parameter_names
+      .as_deref()
+      .ok_or_else(|| "Pivot tables require parameter names".to_string())?;
+    let axis_index = |axis: &str| {
+      names
+        .iter()
+        .position(|name| name == axis)
+        .ok_or_else(|| format!("Unknown axis '{}' (known axes: {})", axis, names.join(", ")))
+    };
+    let row_index = axis_index(&self.rows)?;
+    let column_index = axis_index(&self.columns)?;
+    if row_index == column_index {
+      return Err(format!("Axis '{}' cannot be both the rows and the columns", self.rows));
+    }
+    let mut fixed = Vec::with_capacity(self.fixed.len());
+    for (axis, value) in &self.fixed {
+      let index = axis_index(axis)?;
+      if index == row_index || index == column_index {
+        return Err(format!("Axis '{}' cannot be both fixed and pivoted", axis));
+      }
+      fixed.push((index, value));
+    }
+    let section_indices: Vec<usize> = (0..names.len())
+      .filter(|index| {
+        *index != row_index && *index != column_index && fixed.iter().all(|(fixed_index, _)| fixed_index != index)
+      })
+      .collect();
+
+    let mut format = match &self.value {
+      PivotValue::Time => CellFormat::Time,
+      PivotValue::Throughput(name) => CellFormat::Rate(
+        results
+          .entries
+          .iter()
+          .flat_map(|entry| &entry.throughputs)
+          .find(|throughput| throughput.name == *name)
+          .map(|throughput| format!("{}/s", throughput.unit))
+          .ok_or_else(|| format!("No variant has a throughput named '{}'", name))?,
+      ),
+    };
+    // Ratios are computed over every entry, so a reference excluded by a fixed axis still counts.
+    let values = match &self.reference {
+      Some((axis, reference)) => {
+        format = CellFormat::Ratio;
+        results.relative_to(axis, reference.clone(), &self.value)?
+      }
+      None => results.entries.iter().map(|entry| entry.value(&self.value)).collect(),
+    };
+
+    let entries: Vec<(&PivotEntry, Option<f64>)> = results
+      .entries
+      .iter()
+      .zip(values)
+      .filter(|(entry, _)| entry.combination.cells.len() == names.len())
+      .filter(|(entry, _)| fixed.iter().all(|(index, value)| entry.combination.cells[*index] == **value))
+      .collect();
+    if entries.is_empty() {
+      return Err(format!("Suite '{}' has no results matching the fixed axes", results.suite_name));
+    }
+
+    let mut column_values: Vec<MatrixCellValue> = Vec::new();
+    let mut sections: Vec<Section> = Vec::new();
+    for (entry, _) in &entries {
+      let cells = &entry.combination.cells;
+      push_unique(&mut column_values, &cells[column_index]);
+      let key = entry.combination.select(&section_indices).cells;
+      if !sections.iter().any(|section| section.key == key) {
+        let title = (!key.is_empty()).then(|| describe(&section_indices, names, &key));
+        sections.push(Section {
+          title,
+          key,
+          row_values: Vec::new(),
+          cells: Vec::new(),
+        });
+      }
+    }
+    for section in &mut sections {
+      for (entry, _) in entries.iter().filter(|(entry, _)| entry.combination.select(&section_indices).cells == section.key) {
+        push_unique(&mut section.row_values, &entry.combination.cells[row_index]);
+      }
+      section.cells = vec![vec![None; column_values.len()]; section.row_values.len()];
+    }
+    for (entry, value) in &entries {
+      let cells = &entry.combination.cells;
+      let key = entry.combination.select(&section_indices).cells;
+      let section = sections.iter_mut().find(|section| section.key == key).expect("section was created above");
+      let row = section.row_values.iter().position(|value| *value == cells[row_index]).expect("row was created above");
+      let column = column_values.iter().position(|value| *value == cells[column_index]).expect("column was created above");
+      section.cells[row][column] = *value;
+    }
+
+    let mut heading = format!(
+      "{}: {}",
+      results.suite_name,
+      match &self.value {
+        PivotValue::Time => "mean time per iteration".to_string(),
+        PivotValue::Throughput(name) => format!("{} throughput", name),
+      }
+    );
+    if let Some((axis, reference)) = &self.reference {
+      let _ = write!(heading, " relative to {}={}", axis, plain_text(reference));
+    }
+    if !fixed.is_empty() {
+      let indices: Vec<usize> = fixed.iter().map(|(index, _)| *index).collect();
+      let values: Vec<MatrixCellValue> = fixed.iter().map(|(_, value)| (*value).clone()).collect();
+      let _ = write!(heading, " ({})", describe(&indices, names, &values));
+    }
+
+    Ok(Pivoted {
+      heading,
+      row_axis: names[row_index].clone(),
+      column_axis: names[column_index].clone(),
+      column_values,
+      sections,
+      format,
+    })
+  }
+}
+
+impl Pivoted {
+  fn format(&self, value: Option<f64>) -> String {
+    match (value, &self.format) {
+      (None, _) => "-".to_string(),
+      (Some(secs), CellFormat::Time) => format_duration(Duration::from_secs_f64(secs)),
+      (Some(rate), CellFormat::Rate(unit)) => format!("{} {}", format_rate(rate), unit),
+      (Some(ratio), CellFormat::Ratio) => format!("{}x", two_significant_digits(ratio)),
+    }
+  }
+}
+
+/// Formats `value` to two significant digits, e.g. `2.3`, `0.62` or `15`. Larger values keep
+/// all their integer digits.
+fn two_significant_digits(value: f64) -> String {
+  if !value.is_finite() || value <= 0.0 {
+    return format!("{}", value);
+  }
+  let decimals = |v: f64| (1 - v.log10().floor() as i32).max(0);
+  let scale = 10f64.powi(decimals(value));
+  let rounded = (value * scale).round() / scale;
+  format!("{:.*}", decimals(rounded) as usize, rounded)
+}
+
+fn push_unique(values: &mut Vec<MatrixCellValue>, value: &MatrixCellValue) {
+  if !values.contains(value) {
+    values.push(value.clone());
+  }
+}
+
+/// Renders axis values as `Name=Value, Name=Value`.
+fn describe(indices: &[usize], names: &[String], values: &[MatrixCellValue]) -> String {
+  indices
+    .iter()
+    .zip(values)
+    .map(|(index, value)| format!("{}={}", names[*index], plain_text(value)))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+fn width(text: &str) -> usize {
+  text.chars().count()
+}
+
+fn pad_left(text: &str, to: usize) -> String {
+  format!("{}{}", " ".repeat(to.saturating_sub(width(text))), text)
+}
+
+fn pad_right(text: &str, to: usize) -> String {
+  format!("{}{}", text, " ".repeat(to.saturating_sub(width(text))))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn results() -> PivotResults {
+    let names = ["Order", "Algorithm", "Size"].map(String::from).to_vec();
+    let mut entries = Vec::new();
+    for (order, algorithm, size, nanos) in [
+      ("Sorted", "Quick", 64u64, 300),
+      ("Sorted", "Merge", 64, 200),
+      ("Sorted", "Quick", 128, 900),
+      ("Sorted", "Merge", 128, 1_500),
+      ("Reversed", "Quick", 64, 400),
+    ] {
+      entries.push(PivotEntry {
+        combination: AbstractCombination {
+          cells: vec![order.into(), algorithm.into(), size.into()],
+        },
+        mean_time: Some(Duration::from_nanos(nanos)),
+        throughputs: vec![ThroughputReport {
+          name: "items".to_string(),
+          unit: "elements".to_string(),
+          per_iteration: size as f64,
+          per_second: size as f64 * 1e9 / nanos as f64,
+        }],
+      });
+    }
+    PivotResults {
+      suite_name: "Sort".to_string(),
+      parameter_names: Some(names),
+      entries,
+    }
+  }
+
+  #[test]
+  fn test_markdown_sections_and_fastest_cells() {
+    let markdown = PivotTable::new("Size", "Algorithm").to_markdown(results()).unwrap();
+    assert!(markdown.starts_with("**Sort: mean time per iteration**\n"), "{}", markdown);
+    assert!(markdown.contains("*Order=Sorted*\n\n| Size \\ Algorithm | Quick | Merge |\n|:---|---:|---:|\n"), "{}", markdown);
+    assert!(markdown.contains("| 64 | 300ns | **200ns** |\n| 128 | **900ns** | 1.5us |\n"), "{}", markdown);
+    // A row with a single value has nothing to compare, and a missing cell renders as `-`.
+    assert!(markdown.contains("*Order=Reversed*\n\n| Size \\ Algorithm | Quick | Merge |\n|:---|---:|---:|\n| 64 | 400ns | - |\n"), "{}", markdown);
+
+    let throughput = PivotTable::new("Size", "Algorithm")
+      .value(PivotValue::Throughput("items".to_string()))
+      .fix("Order", "Sorted")
+      .to_markdown(results())
+      .unwrap();
+    assert!(throughput.starts_with("**Sort: items throughput (Order=Sorted)**\n\n|"), "{}", throughput);
+    assert!(throughput.contains("| 64 | 213M elements/s | **320M elements/s** |"), "{}", throughput);
+  }
+
+  #[test]
+  fn test_values_relative_to_a_reference_variant() {
+    let markdown = PivotTable::new("Size", "Algorithm").relative_to("Algorithm", "Quick").to_markdown(results()).unwrap();
+    assert!(markdown.starts_with("**Sort: mean time per iteration relative to Algorithm=Quick**\n"), "{}", markdown);
+    assert!(markdown.contains("| 64 | 1.0x | **0.67x** |\n| 128 | **1.0x** | 1.7x |\n"), "{}", markdown);
+    assert!(markdown.contains("| 64 | 1.0x | - |\n"), "{}", markdown);
+
+    // The reference may be excluded from the table by a fixed axis.
+    let reversed = PivotTable::new("Size", "Algorithm")
+      .fix("Order", "Reversed")
+      .relative_to("Order", "Sorted")
+      .to_markdown(results())
+      .unwrap();
+    assert!(reversed.ends_with("| 64 | 1.3x |\n"), "{}", reversed);
+    assert_eq!(
+      results().relative_to("Algorithm", "Heap", &PivotValue::Time).unwrap_err(),
+      "No variant has Algorithm=Heap"
+    );
+  }
+
+  #[test]
+  fn test_terminal_alignment_and_errors() {
+    let text = PivotTable::new("Size", "Algorithm")
+      .fix("Order", "Sorted")
+      .color(false)
+      .to_terminal(results())
+      .unwrap();
+    let expected = "Sort: mean time per iteration (Order=Sorted)\n\n  Size \\ Algorithm   Quick   Merge\n  64                300ns   200ns*\n  128               900ns*  1.5us \n\n* fastest in row\n";
+    assert_eq!(text, expected);
+    let colored = PivotTable::new("Size", "Algorithm").fix("Order", "Sorted").color(true).to_terminal(results()).unwrap();
+    assert!(colored.contains("  300ns  \x1b[1;32m200ns\x1b[0m\n"), "{:?}", colored);
+
+    let pivot = |rows: &str, columns: &str| PivotTable::new(rows, columns).to_markdown(results()).unwrap_err();
+    assert_eq!(pivot("Size", "Size"), "Axis 'Size' cannot be both the rows and the columns");
+    assert_eq!(pivot("Size", "Backend"), "Unknown axis 'Backend' (known axes: Order, Algorithm, Size)");
+    let unknown_throughput = PivotTable::new("Size", "Algorithm")
+      .value(PivotValue::Throughput("bytes".to_string()))
+      .to_markdown(results());
+    assert_eq!(unknown_throughput.unwrap_err(), "No variant has a throughput named 'bytes'");
+  }
+
+  #[test]
+  fn test_ratios_keep_two_significant_digits() {
+    let formatted: Vec<String> = [1.0, 2.3, 0.625, 0.0625, 9.96, 15.4, 153.0, 0.0]
+      .into_iter()
+      .map(two_significant_digits)
+      .collect();
+    assert_eq!(formatted, ["1.0", "2.3", "0.63", "0.063", "10", "15", "153", "0"]);
+  }
+}
@@ -0,0 +1,519 @@
+//! Resolution of a suite's matrix into the concrete list of benchmark variants it will run.
+//!
+//! Planning applies filters and exclusion constraints, runs the extractor over every remaining
+//! combination and computes each variant's benchmark ID, without calling any setup function or
+//! touching the benchmark harness. The suites use it both to drive their runs and to implement
+//! their list (dry-run) mode.
+
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+use crate::constraint::Constraint;
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+use crate::filter::{apply_filters, plain_text, MatrixFilter};
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+use crate::generator::generate_combinations;
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+use crate::id::IdFormat;
+use crate::params::{AbstractCombination, MatrixCellValue};
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write as _};
+
+/// Name of the environment variable that switches the suites into list mode.
+pub const LIST_ENV_VAR: &str = "BENCH_MATRIX_LIST";
+
+/// Why a combination will not be benchmarked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+  /// The combination matched an exclusion constraint.
+  Excluded,
+  /// The extractor returned an error (rendered with `{:?}`).
+  Extraction(String),
+  /// The user-supplied configuration validator rejected the extracted configuration.
+  InvalidConfig(String),
+  /// Another runnable combination earlier in the plan has the same benchmark ID.
+  /// Holds that combination's cells (rendered with `{:?}`).
+  DuplicateId(String),
+}
+
+impl fmt::Display for SkipReason {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SkipReason::Excluded => write!(f, "excluded by constraint"),
+      SkipReason::Extraction(e) => write!(f, "extraction failed: {}", e),
+      SkipReason::InvalidConfig(e) => write!(f, "invalid configuration: {}", e),
+      SkipReason::DuplicateId(first) => write!(
+        f,
+        "benchmark ID already used by {}; consider IdFormat::Reversible",
+        first
+      ),
+    }
+  }
+}
+
+/// One combination of a suite's matrix together with its benchmark ID and resolved configuration.
+#[derive(Debug, Clone)]
+pub struct PlannedVariant<Cfg> {
+  /// The abstract combination this variant was generated from.
+  pub combination: AbstractCombination,
+  /// The Criterion group this variant is registered in: the suite name, followed by the
+  /// values of any `group_by` axes (e.g. `Suite/Backend-Uring`).
+  pub group_name: String,
+  /// The benchmark ID within the group, built from the axes that are neither grouped nor the
+  /// x-axis (e.g. `Backend-Uring_BlockSize-512`). Empty if no such axis is left.
+  pub bench_id: String,
+  /// The value of the x-axis, used as the Criterion benchmark parameter, if one is set.
+  pub parameter: Option<String>,
+  /// The extracted configuration, or the reason this variant will be skipped.
+  pub config: Result<Cfg, SkipReason>,
+}
+
+impl<Cfg> PlannedVariant<Cfg> {
+  /// Returns the full ID as Criterion reports it, e.g. `Suite/Backend-Uring/Mode-Low/512`.
+  pub fn full_id(&self) -> String {
+    join_id(&self.group_name, &self.bench_id, self.parameter.as_deref())
+  }
+
+  /// Returns whether this variant will be handed to the benchmark harness
+  /// (subject to its global setup succeeding).
+  pub fn is_runnable(&self) -> bool {
+    self.config.is_ok()
+  }
+}
+
+/// The resolved list of variants for a suite.
+#[derive(Debug, Clone)]
+pub struct MatrixPlan<Cfg> {
+  /// The suite (Criterion group) name.
+  pub suite_name: String,
+  /// The axis names used for IDs, if set.
+  pub parameter_names: Option<Vec<String>>,
+  /// The axes after filters were applied.
+  pub parameter_axes: Vec<Vec<MatrixCellValue>>,
+  /// Every generated combination, in generation order.
+  pub variants: Vec<PlannedVariant<Cfg>>,
+  /// Non-fatal problems found while planning (e.g. constraints naming unknown axes).
+  pub warnings: Vec<String>,
+}
+
+impl<Cfg> MatrixPlan<Cfg> {
+  /// Returns the number of variants that will be benchmarked.
+  pub fn runnable_count(&self) -> usize {
+    self.variants.iter().filter(|variant| variant.is_runnable()).count()
+  }
+
+  /// Returns the number of variants that will be skipped.
+  pub fn skipped_count(&self) -> usize {
+    self.variants.len() - self.runnable_count()
+  }
+
+  /// Returns the full benchmark IDs shared by more than one variant that would otherwise run
+  /// (runnable or refused as a duplicate), each with the indices (into `variants`) of the
+  /// variants using it, in order of first appearance.
+  pub fn duplicate_ids(&self) -> Vec<(String, Vec<usize>)> {
+    let mut seen: Vec<(String, Vec<usize>)> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for (index, variant) in self.variants.iter().enumerate() {
+      if !matches!(variant.config, Ok(_) | Err(SkipReason::DuplicateId(_))) {
+        continue;
+      }
+      let id = variant.full_id();
+      match positions.get(&id) {
+        Some(&position) => seen[position].1.push(index),
+        None => {
+          positions.insert(id.clone(), seen.len());
+          seen.push((id, vec![index]));
+        }
+      }
+    }
+    seen.retain(|(_, indices)| indices.len() > 1);
+    seen
+  }
+
+  /// Renders the plan as an aligned text table of benchmark IDs and their status.
+  pub fn render_table(&self) -> String {
+    let ids: Vec<String> = self.variants.iter().map(PlannedVariant::full_id).collect();
+    let id_width = ids.iter().map(String::len).max().unwrap_or(0).max("Benchmark ID".len());
+    let index_width = self.variants.len().to_string().len().max(1);
+
+    let mut out = String::new();
+    let _ = writeln!(
+      out,
+      "Suite '{}': {} combinations, {} to run, {} skipped",
+      self.suite_name,
+      self.variants.len(),
+      self.runnable_count(),
+      self.skipped_count()
+    );
+    let _ = writeln!(out, "  {:>index_width$}  {:<id_width$}  Status", "#", "Benchmark ID");
+    for (index, (variant, id)) in self.variants.iter().zip(&ids).enumerate() {
+      let status = match &variant.config {
+        Ok(_) => "run".to_string(),
+        Err(reason) => format!("skipped ({})", reason),
+      };
+      let _ = writeln!(out, "  {:>index_width$}  {:<id_width$}  {}", index + 1, id, status);
+    }
+    out
+  }
+}
+
+/// Borrowed form of a suite's configuration validator.
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+type ConfigCheck<'a, Cfg> = &'a dyn Fn(&Cfg) -> Result<(), String>;
+
+/// The parts of a suite definition needed to build a [`MatrixPlan`].
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+pub(crate) struct PlanSource<'a, Cfg, ExtErr> {
+  pub suite_name: &'a str,
+  pub parameter_names: Option<&'a [String]>,
+  pub parameter_axes: &'a [Vec<MatrixCellValue>],
+  pub filters: &'a [MatrixFilter],
+  pub exclusions: &'a [Constraint],
+  pub extractor: &'a dyn Fn(&AbstractCombination) -> Result<Cfg, ExtErr>,
+  pub config_validator: Option<ConfigCheck<'a, Cfg>>,
+  pub id_format: &'a IdFormat,
+  pub group_by: &'a [String],
+  pub x_axis: Option<&'a str>,
+}
+
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+impl<Cfg, ExtErr: Debug> PlanSource<'_, Cfg, ExtErr> {
+  /// Builds the plan. Fails only if the filters cannot be applied, in which case
+  /// nothing from the suite should run.
+  ///
+  /// A runnable variant whose benchmark ID was already taken by an earlier runnable variant
+  /// is refused with [`SkipReason::DuplicateId`], as Criterion would otherwise merge them.
+  /// With `group_by` axes, variants are ordered so that each Criterion group is contiguous.
+  pub fn plan(&self) -> Result<MatrixPlan<Cfg>, String> {
+    let mut warnings = Vec::new();
+    let parameter_axes = apply_filters(self.filters, self.parameter_names, self.parameter_axes, &mut warnings)?;
+
+    let exclusion_names: &[String] = match self.parameter_names {
+      Some(names) => {
+        for constraint in self.exclusions {
+          if let Err(e) = constraint.check_axes(names) {
+            warnings.push(format!("{}. This exclusion will be ignored.", e));
+          }
+        }
+        names
+      }
+      None => {
+        if !self.exclusions.is_empty() {
+          warnings.push(format!(
+            "Exclusion constraints require parameter names. {} exclusion(s) will be ignored.",
+            self.exclusions.len()
+          ));
+        }
+        &[]
+      }
+    };
+
+    let layout = GroupLayout::resolve(self.group_by, self.x_axis, self.parameter_names, parameter_axes.len())
+      .unwrap_or_else(|e| {
+        warnings.push(format!("{}. Grouping will be ignored.", e));
+        GroupLayout::flat(self.parameter_names, parameter_axes.len())
+      });
+
+    let id_format = match self.id_format {
+      IdFormat::Template(template) => match template.check_axes(layout.rest_names.as_deref()) {
+        Ok(()) => self.id_format,
+        Err(e) => {
+          warnings.push(format!("{}. Readable IDs will be used instead.", e));
+          &IdFormat::Readable
+        }
+      },
+      other => other,
+    };
+
+    // Group names use the readable or reversible `Name-Value` form; templates only lay out the
+    // benchmark IDs within each group.
+    let group_format = match id_format {
+      IdFormat::Reversible => &IdFormat::Reversible,
+      _ => &IdFormat::Readable,
+    };
+
+    let mut first_with_id: HashMap<String, String> = HashMap::new();
+    let mut variants: Vec<PlannedVariant<Cfg>> = generate_combinations(&parameter_axes)
+      .map(|combination| {
+        let group_name = if layout.group_indices.is_empty() {
+          self.suite_name.to_string()
+        } else {
+          let group_combination = combination.select(&layout.group_indices);
+          format!(
+            "{}/{}",
+            self.suite_name,
+            group_format.format(&group_combination, layout.group_names.as_deref())
+          )
+        };
+        let bench_id = if layout.rest_indices.is_empty() {
+          String::new()
+        } else {
+          id_format.format(&combination.select(&layout.rest_indices), layout.rest_names.as_deref())
+        };
+        let parameter = layout.x_index.map(|index| plain_text(&combination.cells[index]));
+        let full_id = join_id(&group_name, &bench_id, parameter.as_deref());
+        let excluded = self
+          .exclusions
+          .iter()
+          .any(|constraint| constraint.matches(&combination, exclusion_names).unwrap_or(false));
+        let config = if excluded {
+          Err(SkipReason::Excluded)
+        } else {
+          (self.extractor)(&combination)
+            .map_err(|e| SkipReason::Extraction(format!("{:?}", e)))
+            .and_then(|cfg| match self.config_validator {
+              Some(validator) => validator(&cfg).map(|()| cfg).map_err(SkipReason::InvalidConfig),
+              None => Ok(cfg),
+            })
+            .and_then(|cfg| match first_with_id.get(&full_id) {
+              Some(first) => Err(SkipReason::DuplicateId(first.clone())),
+              None => {
+                first_with_id.insert(full_id, format!("{:?}", combination.cells));
+                Ok(cfg)
+              }
+            })
+        };
+        PlannedVariant {
+          combination,
+          group_name,
+          bench_id,
+          parameter,
+          config,
+        }
+      })
+      .collect();
+
+    // Criterion groups cannot be reopened once finished, so keep each group's variants together.
+    let mut group_order: HashMap<String, usize> = HashMap::new();
+    for variant in &variants {
+      let next = group_order.len();
+      group_order.entry(variant.group_name.clone()).or_insert(next);
+    }
+    variants.sort_by_key(|variant| group_order[&variant.group_name]);
+
+    Ok(MatrixPlan {
+      suite_name: self.suite_name.to_string(),
+      parameter_names: self.parameter_names.map(<[String]>::to_vec),
+      parameter_axes,
+      variants,
+      warnings,
+    })
+  }
+}
+
+/// Joins the parts of a full benchmark ID, leaving out an empty benchmark ID.
+fn join_id(group_name: &str, bench_id: &str, parameter: Option<&str>) -> String {
+  let mut id = group_name.to_string();
+  for part in Some(bench_id).filter(|part| !part.is_empty()).into_iter().chain(parameter) {
+    id.push('/');
+    id.push_str(part);
+  }
+  id
+}
+
+/// Which axes of a suite form the Criterion group name, the x-axis and the benchmark ID.
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+struct GroupLayout {
+  group_indices: Vec<usize>,
+  group_names: Option<Vec<String>>,
+  x_index: Option<usize>,
+  rest_indices: Vec<usize>,
+  rest_names: Option<Vec<String>>,
+}
+
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+impl GroupLayout {
+  /// Every axis contributes to the benchmark ID, all in one group.
+  fn flat(parameter_names: Option<&[String]>, axis_count: usize) -> Self {
+    Self {
+      group_indices: Vec::new(),
+      group_names: None,
+      x_index: None,
+      rest_indices: (0..axis_count).collect(),
+      rest_names: parameter_names.map(<[String]>::to_vec),
+    }
+  }
+
+  fn resolve(
+    group_by: &[String],
+    x_axis: Option<&str>,
+    parameter_names: Option<&[String]>,
+    axis_count: usize,
+  ) -> Result<Self, String> {
+    if group_by.is_empty() && x_axis.is_none() {
+      return Ok(Self::flat(parameter_names, axis_count));
+    }
+    let names = parameter_names.ok_or_else(|| "group_by and x_axis require parameter names".to_string())?;
+    let index_of = |axis: &str| {
+      names
+        .iter()
+        .position(|name| name == axis)
+        .ok_or_else(|| format!("Unknown axis '{}' (known axes: {})", axis, names.join(", ")))
+    };
+
+    let mut group_indices = Vec::with_capacity(group_by.len());
+    for axis in group_by {
+      let index = index_of(axis)?;
+      if group_indices.contains(&index) {
+        return Err(format!("Axis '{}' is listed more than once in group_by", axis));
+      }
+      group_indices.push(index);
+    }
+    let x_index = x_axis.map(index_of).transpose()?;
+    if let Some(index) = x_index
+      && group_indices.contains(&index)
+    {
+      return Err(format!("Axis '{}' cannot be both grouped and the x-axis", names[index]));
+    }
+    let rest_indices: Vec<usize> = (0..axis_count)
+      .filter(|index| !group_indices.contains(index) && x_index != Some(*index))
+      .collect();
+    if rest_indices.is_empty() && x_index.is_none() {
+      return Err("group_by covers every axis, leaving nothing to tell benchmarks apart; set an x_axis".to_string());
+    }
+
+    Ok(Self {
+      group_names: Some(group_indices.iter().map(|&index| names[index].clone()).collect()),
+      rest_names: Some(rest_indices.iter().map(|&index| names[index].clone()).collect()),
+      group_indices,
+      x_index,
+      rest_indices,
+    })
+  }
+}
+
+/// Returns whether list mode was requested through `BENCH_MATRIX_LIST`. Criterion's own
+/// `--list` flag is left to Criterion, whose output cargo and IDE test explorers parse.
+#[cfg(feature = "criterion_integration")]
+pub(crate) fn list_mode_requested() -> bool {
+  std::env::var(LIST_ENV_VAR)
+    .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+    .unwrap_or(false)
+}
+
+#[cfg(all(test, any(feature = "criterion_integration", feature = "standalone")))]
+mod tests {
+  use super::*;
+
+  fn extract(combo: &AbstractCombination) -> Result<u64, String> {
+    match combo.get_u64(1)? {
+      0 => Err("zero is not allowed".to_string()),
+      n => Ok(n),
+    }
+  }
+
+  #[test]
+  fn test_plan_reports_exclusions_and_extraction_failures() {
+    let names = vec!["Backend".to_string(), "Size".to_string()];
+    let axes = vec![
+      vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Tag("Epoll".to_string())],
+      vec![MatrixCellValue::Unsigned(0), MatrixCellValue::Unsigned(64)],
+    ];
+    let exclusions = vec![Constraint::new().when("Backend", ["Epoll"]).when("Size", [64u64])];
+    let source = PlanSource {
+      suite_name: "Suite",
+      parameter_names: Some(&names),
+      parameter_axes: &axes,
+      filters: &[],
+      exclusions: &exclusions,
+      extractor: &extract,
+      config_validator: None,
+      id_format: &IdFormat::Readable,
+      group_by: &[],
+      x_axis: None,
+    };
+
+    let plan = source.plan().unwrap();
+    let statuses: Vec<(&str, Result<u64, SkipReason>)> = plan
+      .variants
+      .iter()
+      .map(|variant| (variant.bench_id.as_str(), variant.config.clone()))
+      .collect();
+    assert_eq!(
+      statuses,
+      vec![
+        ("Backend-Uring_Size-0", Err(SkipReason::Extraction("\"zero is not allowed\"".to_string()))),
+        ("Backend-Uring_Size-64", Ok(64)),
+        ("Backend-Epoll_Size-0", Err(SkipReason::Extraction("\"zero is not allowed\"".to_string()))),
+        ("Backend-Epoll_Size-64", Err(SkipReason::Excluded)),
+      ]
+    );
+    assert_eq!(plan.runnable_count(), 1);
+
+    let table = plan.render_table();
+    assert!(table.starts_with("Suite 'Suite': 4 combinations, 1 to run, 3 skipped"));
+    assert!(table.contains("Suite/Backend-Epoll_Size-64  skipped (excluded by constraint)"));
+  }
+
+  #[test]
+  fn test_plan_refuses_duplicate_ids() {
+    let names = vec!["Offset".to_string()];
+    let axes = vec![vec![MatrixCellValue::Int(5), MatrixCellValue::Unsigned(5)]];
+    let extractor = |_: &AbstractCombination| Ok::<_, String>(());
+    let mut source = PlanSource {
+      suite_name: "Suite",
+      parameter_names: Some(&names),
+      parameter_axes: &axes,
+      filters: &[],
+      exclusions: &[],
+      extractor: &extractor,
+      config_validator: None,
+      id_format: &IdFormat::Readable,
+      group_by: &[],
+      x_axis: None,
+    };
+
+    let plan = source.plan().unwrap();
+    assert_eq!(plan.runnable_count(), 1);
+    assert_eq!(plan.variants[1].config, Err(SkipReason::DuplicateId("[Int(5)]".to_string())));
+    assert_eq!(plan.duplicate_ids(), vec![("Suite/Offset-5".to_string(), vec![0, 1])]);
+
+    source.id_format = &IdFormat::Reversible;
+    let plan = source.plan().unwrap();
+    assert_eq!(plan.runnable_count(), 2);
+    assert_eq!(plan.variants[0].bench_id, "Offset-+5");
+    assert_eq!(plan.variants[1].bench_id, "Offset-5");
+  }
+
+  #[test]
+  fn test_plan_groups_by_axis_with_x_axis_parameter() {
+    let names = vec!["Size".to_string(), "Backend".to_string(), "Mode".to_string()];
+    let axes = vec![
+      vec![MatrixCellValue::Unsigned(64), MatrixCellValue::Unsigned(512)],
+      vec![MatrixCellValue::Tag("Uring".to_string()), MatrixCellValue::Tag("Epoll".to_string())],
+      vec![MatrixCellValue::Tag("Low".to_string())],
+    ];
+    let extractor = |_: &AbstractCombination| Ok::<_, String>(());
+    let group_by = vec!["Backend".to_string()];
+    let mut source = PlanSource {
+      suite_name: "Suite",
+      parameter_names: Some(&names),
+      parameter_axes: &axes,
+      filters: &[],
+      exclusions: &[],
+      extractor: &extractor,
+      config_validator: None,
+      id_format: &IdFormat::Readable,
+      group_by: &group_by,
+      x_axis: Some("Size"),
+    };
+
+    let plan = source.plan().unwrap();
+    let ids: Vec<String> = plan.variants.iter().map(PlannedVariant::full_id).collect();
+    assert_eq!(
+      ids,
+      vec![
+        "Suite/Backend-Uring/Mode-Low/64",
+        "Suite/Backend-Uring/Mode-Low/512",
+        "Suite/Backend-Epoll/Mode-Low/64",
+        "Suite/Backend-Epoll/Mode-Low/512",
+      ]
+    );
+    assert_eq!(plan.variants[1].parameter.as_deref(), Some("512"));
+    assert!(plan.warnings.is_empty());
+
+    source.x_axis = Some("Backend");
+    let plan = source.plan().unwrap();
+    assert_eq!(plan.variants[0].full_id(), "Suite/Size-64_Backend-Uring_Mode-Low");
+    assert!(plan.warnings[0].contains("cannot be both grouped and the x-axis"), "{:?}", plan.warnings);
+  }
+}
@@ -0,0 +1,226 @@
+//! bench_matrix's own per-variant results, returned by the suites' `run()`.
+//!
+//! Criterion keeps its own statistics and reports; this report adds what Criterion cannot
+//! express, such as several throughput dimensions for one measurement or throughput computed
+//! from the state after the run. Timings are accumulated over every batch Criterion ran,
+//! warm-up included, so they give a whole-run average rather than Criterion's estimate.
+
+use crate::id::format_duration;
+use crate::latency::LatencySummary;
+use crate::metrics::MetricSummary;
+use crate::params::AbstractCombination;
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// One throughput dimension of a variant, e.g. bytes/s or messages/s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThroughputReport {
+  /// The name given when the throughput was declared (e.g. `messages`).
+  pub name: String,
+  /// What is counted, e.g. `bytes`, `bits` or `elements`.
+  pub unit: String,
+  /// The mean amount processed by one iteration.
+  pub per_iteration: f64,
+  /// The amount processed per second of measured time, averaged over the whole run
+  /// (warm-up included).
+  pub per_second: f64,
+}
+
+/// The results of one benchmarked variant.
+#[derive(Debug, Clone)]
+pub struct VariantReport {
+  /// The full benchmark ID, as Criterion reports it.
+  pub id: String,
+  /// The combination this variant was generated from.
+  pub combination: AbstractCombination,
+  /// The number of iterations run, summed over every batch (warm-up included).
+  pub iterations: u64,
+  /// The measured time of those iterations, warm-up included.
+  pub total_time: Duration,
+  /// Every throughput declared for the suite, the primary one first.
+  pub throughputs: Vec<ThroughputReport>,
+  /// The aggregates of the metrics recorded by the suite's `collect_metrics` hook.
+  pub metrics: Vec<MetricSummary>,
+  /// The distribution of per-operation latencies recorded by a recording logic function, or
+  /// of request latencies in an open-loop run.
+  pub latency: Option<LatencySummary>,
+  /// The request counts and rates of an open-loop run.
+  pub open_loop: Option<OpenLoopSummary>,
+}
+
+/// The request counts and rates of one variant run by `AsyncBenchmarkSuite::run_open_loop`.
+/// For such variants, `VariantReport::iterations` counts completed requests and
+/// `total_time` is the wall-clock duration of the run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenLoopSummary {
+  /// The requested rate, in requests per second.
+  pub target_rate: f64,
+  /// Completed requests per second of wall-clock time.
+  pub achieved_rate: f64,
+  pub sent: u64,
+  pub completed: u64,
+  /// Requests whose future panicked.
+  pub failed: u64,
+}
+
+impl VariantReport {
+  /// Returns the mean time per iteration over the whole run, warm-up included, or `None` if
+  /// nothing was measured. Criterion's own estimate covers the sample batches only.
+  pub fn run_mean_time(&self) -> Option<Duration> {
+    if self.iterations == 0 {
+      return None;
+    }
+    Some(Duration::from_secs_f64(self.total_time.as_secs_f64() / self.iterations as f64))
+  }
+
+  /// Returns the aggregate of the metric recorded under `name`, if any.
+  pub fn metric(&self, name: &str) -> Option<&MetricSummary> {
+    self.metrics.iter().find(|metric| metric.name == name)
+  }
+
+  /// Returns the throughput declared under `name`, if any.
+  pub fn throughput(&self, name: &str) -> Option<&ThroughputReport> {
+    self.throughputs.iter().find(|throughput| throughput.name == name)
+  }
+}
+
+/// The results of a whole suite run.
+#[derive(Debug, Clone, Default)]
+pub struct SuiteReport {
+  /// The suite (base Criterion group) name.
+  pub suite_name: String,
+  /// The suite's axis names, if set, in axis order.
+  pub parameter_names: Option<Vec<String>>,
+  /// One report per benchmarked variant, in run order. Skipped variants, and variants
+  /// deselected by Criterion's command-line filter, are not included.
+  pub variants: Vec<VariantReport>,
+}
+
+impl SuiteReport {
+  /// Returns the report of the variant with the given full benchmark ID.
+  pub fn variant(&self, id: &str) -> Option<&VariantReport> {
+    self.variants.iter().find(|variant| variant.id == id)
+  }
+
+  /// Renders the report as one line per variant with its run mean time and throughputs,
+  /// followed by indented lines for its open-loop rates, latency distribution and each
+  /// recorded metric.
+  pub fn render(&self) -> String {
+    let id_width = self.variants.iter().map(|variant| variant.id.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    let _ = writeln!(out, "Suite '{}' report:", self.suite_name);
+    for variant in &self.variants {
+      let mean = variant
+        .run_mean_time()
+        .map(format_duration)
+        .unwrap_or_else(|| "-".to_string());
+      let _ = write!(out, "  {:<id_width$}  run mean {:>10}", variant.id, mean);
+      for throughput in &variant.throughputs {
+        let _ = write!(
+          out,
+          "  {}: {} {}/s",
+          throughput.name,
+          format_rate(throughput.per_second),
+          throughput.unit
+        );
+      }
+      out.push('\n');
+      if let Some(open_loop) = &variant.open_loop {
+        let _ = writeln!(
+          out,
+          "      open loop: target {} req/s achieved {} req/s ({} sent, {} completed, {} failed)",
+          format_rate(open_loop.target_rate),
+          format_rate(open_loop.achieved_rate),
+          open_loop.sent,
+          open_loop.completed,
+          open_loop.failed
+        );
+      }
+      if let Some(latency) = &variant.latency {
+        let _ = writeln!(
+          out,
+          "      latency: p50 {} p90 {} p99 {} p999 {} max {} (n={})",
+          format_duration(latency.p50),
+          format_duration(latency.p90),
+          format_duration(latency.p99),
+          format_duration(latency.p999),
+          format_duration(latency.max),
+          latency.count
+        );
+      }
+      for metric in &variant.metrics {
+        let _ = writeln!(
+          out,
+          "      {}: mean {} min {} p50 {} p90 {} p99 {} max {} (n={})",
+          metric.name,
+          format_value(metric.mean),
+          format_value(metric.min),
+          format_value(metric.p50),
+          format_value(metric.p90),
+          format_value(metric.p99),
+          format_value(metric.max),
+          metric.count
+        );
+      }
+    }
+    out
+  }
+}
+
+/// Formats a rate with an SI suffix and three significant digits, e.g. `1.23M`.
+pub(crate) fn format_rate(rate: f64) -> String {
+  const SUFFIXES: [&str; 5] = ["", "K", "M", "G", "T"];
+  let mut scaled = rate;
+  let mut suffix = 0;
+  while scaled.abs() >= 1000.0 && suffix + 1 < SUFFIXES.len() {
+    scaled /= 1000.0;
+    suffix += 1;
+  }
+  let precision = match scaled.abs() {
+    s if s >= 100.0 => 0,
+    s if s >= 10.0 => 1,
+    _ => 2,
+  };
+  format!("{:.*}{}", precision, scaled, SUFFIXES[suffix])
+}
+
+/// Formats a metric value with at most four decimals and no trailing zeros.
+fn format_value(value: f64) -> String {
+  let text = format!("{:.4}", value);
+  text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_run_mean_time_and_render() {
+    let report = SuiteReport {
+      suite_name: "Net".to_string(),
+      parameter_names: None,
+      variants: vec![VariantReport {
+        id: "Net/Size-64".to_string(),
+        combination: AbstractCombination { cells: vec![] },
+        iterations: 4,
+        total_time: Duration::from_micros(2),
+        throughputs: vec![ThroughputReport {
+          name: "payload".to_string(),
+          unit: "bytes".to_string(),
+          per_iteration: 64.0,
+          per_second: 128_000_000.0,
+        }],
+        metrics: vec![],
+        latency: None,
+        open_loop: None,
+      }],
+    };
+    let variant = report.variant("Net/Size-64").unwrap();
+    assert_eq!(variant.run_mean_time(), Some(Duration::from_nanos(500)));
+    assert_eq!(variant.throughput("payload").unwrap().per_iteration, 64.0);
+    assert!(report.render().contains("run mean      500ns"), "{}", report.render());
+    assert!(report.render().contains("payload: 128M bytes/s"), "{}", report.render());
+    assert_eq!(format_rate(1234.0), "1.23K");
+  }
+}
@@ -0,0 +1,491 @@
+#![cfg(feature = "standalone")]
+
+//! A minimal runner that executes a matrix without Criterion.
+//!
+//! Each variant is warmed up, then measured over a fixed number of samples, and reported with
+//! the mean, median, standard deviation, minimum and maximum time per iteration. There is no
+//! outlier analysis, no comparison with earlier runs and no plotting, which makes it suitable
+//! for quick smoke runs, `#[test]` functions and environments where Criterion is unwanted. The
+//! setup, logic and teardown functions have the same signatures as those of
+//! `SyncBenchmarkSuite`, so the same functions can drive both.
+//!
+//! # Example
+//! ```
+//! use bench_matrix::standalone::StandaloneSuite;
+//! use bench_matrix::{AbstractCombination, MatrixCellValue};
+//! use std::time::{Duration, Instant};
+//!
+//! fn setup(size: &u64) -> Result<((), Vec<u64>), String> {
+//!   Ok(((), (0..*size).collect()))
+//! }
+//!
+//! fn logic(ctx: (), data: Vec<u64>, _size: &u64) -> ((), Vec<u64>, Duration) {
+//!   let start = Instant::now();
+//!   std::hint::black_box(data.iter().sum::<u64>());
+//!   (ctx, data, start.elapsed())
+//! }
+//!
+//! let report = StandaloneSuite::new(
+//!   "Sum".to_string(),
+//!   Some(vec!["Size".to_string()]),
+//!   vec![vec![MatrixCellValue::Unsigned(16), MatrixCellValue::Unsigned(1024)]],
+//!   |combo: &AbstractCombination| combo.get_u64(0),
+//!   setup,
+//!   logic,
+//!   |_ctx, _data, _size| {},
+//! )
+//! .samples(5)
+//! .run();
+//!
+//! assert_eq!(report.variants.len(), 2);
+//! assert!(report.variant("Sum/Size-1024").unwrap().stats.max >= report.variant("Sum/Size-1024").unwrap().stats.min);
+//! ```
+
+use crate::constraint::Constraint;
+use crate::filter::MatrixFilter;
+use crate::id::{format_duration, IdFormat};
+use crate::params::{AbstractCombination, MatrixCellValue};
+use crate::plan::{MatrixPlan, PlanSource};
+use crate::test_mode::{run_case, skip_outcome, TestReport};
+use crate::validate::{validate_plan, ValidationError};
+
+use std::fmt::{Debug, Write as _};
+use std::time::Duration;
+
+pub type StandaloneSetupFn<S, Cfg, CtxT, SetupErr = String> = fn(&Cfg) -> Result<(CtxT, S), SetupErr>;
+pub type StandaloneLogicFn<S, Cfg, CtxT> = fn(CtxT, S, &Cfg) -> (CtxT, S, Duration);
+pub type StandaloneTeardownFn<S, Cfg, CtxT> = fn(CtxT, S, &Cfg);
+
+type Extractor<Cfg, ExtErr> = Box<dyn Fn(&AbstractCombination) -> Result<Cfg, ExtErr>>;
+
+/// Runs a parameter matrix of synchronous benchmarks without Criterion.
+pub struct StandaloneSuite<S, Cfg, CtxT, ExtErr = String, SetupErr = String> {
+  suite_base_name: String,
+  parameter_names: Option<Vec<String>>,
+  parameter_axes: Vec<Vec<MatrixCellValue>>,
+  extractor_fn: Extractor<Cfg, ExtErr>,
+  setup_fn: StandaloneSetupFn<S, Cfg, CtxT, SetupErr>,
+  benchmark_logic_fn: StandaloneLogicFn<S, Cfg, CtxT>,
+  teardown_fn: StandaloneTeardownFn<S, Cfg, CtxT>,
+  exclusions: Vec<Constraint>,
+  filters: Vec<MatrixFilter>,
+  id_format: IdFormat,
+  warm_up_iterations: u64,
+  samples: usize,
+  iterations_per_sample: u64,
+}
+
+impl<S, Cfg, CtxT, ExtErr, SetupErr> StandaloneSuite<S, Cfg, CtxT, ExtErr, SetupErr>
+where
+  Cfg: Debug,
+  ExtErr: Debug,
+  SetupErr: Debug,
+{
+  /// Creates a suite that runs 3 warm-up iterations and 10 samples of one iteration each per
+  /// variant. Mismatched parameter names are ignored, as in the Criterion suites.
+  pub fn new(
+    suite_base_name: String,
+    parameter_names: Option<Vec<String>>,
+    parameter_axes: Vec<Vec<MatrixCellValue>>,
+    extractor_fn: impl Fn(&AbstractCombination) -> Result<Cfg, ExtErr> + 'static,
+    setup_fn: StandaloneSetupFn<S, Cfg, CtxT, SetupErr>,
+    benchmark_logic_fn: StandaloneLogicFn<S, Cfg, CtxT>,
+    teardown_fn: StandaloneTeardownFn<S, Cfg, CtxT>,
+  ) -> Self {
+    let parameter_names = match parameter_names {
+      Some(names) if names.len() != parameter_axes.len() => {
+        eprintln!(
+          "[BenchMatrix::Standalone] [WARN] Suite '{}': Mismatch between number of parameter_names ({}) and parameter_axes ({}). Parameter names will be ignored for ID generation.",
+          suite_base_name,
+          names.len(),
+          parameter_axes.len()
+        );
+        None
+      }
+      names => names,
+    };
+
+    Self {
+      suite_base_name,
+      parameter_names,
+      parameter_axes,
+      extractor_fn: Box::new(extractor_fn),
+      setup_fn,
+      benchmark_logic_fn,
+      teardown_fn,
+      exclusions: Vec::new(),
+      filters: Vec::new(),
+      id_format: IdFormat::default(),
+      warm_up_iterations: 3,
+      samples: 10,
+      iterations_per_sample: 1,
+    }
+  }
+
+  /// Sets how many iterations run, unmeasured, before the samples of each variant. They get
+  /// their own setup and teardown.
+  pub fn warm_up_iterations(mut self, iterations: u64) -> Self {
+    self.warm_up_iterations = iterations;
+    self
+  }
+
+  /// Sets how many samples are measured per variant.
+  pub fn samples(mut self, samples: usize) -> Self {
+    self.samples = samples;
+    self
+  }
+
+  /// Sets how many iterations each sample runs. Every sample gets a fresh setup and teardown,
+  /// and its time per iteration is the sum of the logic function's durations divided by this.
+  pub fn iterations_per_sample(mut self, iterations: u64) -> Self {
+    self.iterations_per_sample = iterations;
+    self
+  }
+
+  /// Excludes every combination matching `constraint`. Requires parameter names.
+  pub fn exclude(mut self, constraint: Constraint) -> Self {
+    self.exclusions.push(constraint);
+    self
+  }
+
+  /// Narrows (or overrides) axis values before any combination is generated. The
+  /// `BENCH_MATRIX_FILTER` environment variable and `--matrix` flags are applied on top of any
+  /// filters set here.
+  pub fn filter(mut self, filter: MatrixFilter) -> Self {
+    self.filters.push(filter);
+    self
+  }
+
+  /// Sets how combinations are turned into benchmark IDs.
+  pub fn id_format(mut self, format: IdFormat) -> Self {
+    self.id_format = format;
+    self
+  }
+
+  /// Resolves the suite's matrix without running anything.
+  pub fn plan(&self) -> Result<MatrixPlan<Cfg>, String> {
+    PlanSource {
+      suite_name: &self.suite_base_name,
+      parameter_names: self.parameter_names.as_deref(),
+      parameter_axes: &self.parameter_axes,
+      filters: &self.filters,
+      exclusions: &self.exclusions,
+      extractor: &*self.extractor_fn,
+      config_validator: None,
+      id_format: &self.id_format,
+      group_by: &[],
+      x_axis: None,
+    }
+    .plan()
+  }
+
+  /// Checks the whole matrix before anything runs and returns every problem at once, as the
+  /// Criterion suites' `validate()` does. No setup function is called.
+  pub fn validate(&self) -> Result<(), ValidationError> {
+    validate_plan(
+      &self.suite_base_name,
+      self.parameter_names.as_deref(),
+      self.parameter_axes.len(),
+      self.plan(),
+    )
+  }
+
+  /// Runs every variant, prints the results as a table and returns them. Variants that cannot
+  /// be extracted or set up are listed in the report's `skipped`; if the suite cannot be
+  /// planned, nothing runs.
+  pub fn run(self) -> StandaloneReport {
+    let mut report = StandaloneReport {
+      suite_name: self.suite_base_name.clone(),
+      parameter_names: self.parameter_names.clone(),
+      variants: Vec::new(),
+      skipped: Vec::new(),
+    };
+
+    let plan = match self.check_settings().and_then(|()| self.plan()) {
+      Ok(plan) => plan,
+      Err(e) => {
+        eprintln!(
+          "[BenchMatrix::Standalone] [ERROR] Suite '{}': {}. Skipping this suite.",
+          self.suite_base_name, e
+        );
+        return report;
+      }
+    };
+    for warning in &plan.warnings {
+      eprintln!("[BenchMatrix::Standalone] [WARN] Suite '{}': {}", self.suite_base_name, warning);
+    }
+
+    for variant in plan.variants {
+      let full_id = variant.full_id();
+      let cfg = match variant.config {
+        Ok(cfg) => cfg,
+        Err(reason) => {
+          report.skipped.push((full_id, reason.to_string()));
+          continue;
+        }
+      };
+
+      if self.warm_up_iterations > 0
+        && let Err(e) = self.run_batch(&cfg, self.warm_up_iterations)
+      {
+        eprintln!(
+          "[BenchMatrix::Standalone] [ERROR] Suite '{}', Benchmark ID '{}': Setup failed: {:?}. Skipping this configuration.",
+          self.suite_base_name, full_id, e
+        );
+        report.skipped.push((full_id, format!("setup failed: {:?}", e)));
+        continue;
+      }
+
+      let mut samples = Vec::with_capacity(self.samples);
+      let mut setup_error = None;
+      for _ in 0..self.samples {
+        match self.run_batch(&cfg, self.iterations_per_sample) {
+          Ok(elapsed) => samples.push(elapsed / self.iterations_per_sample as u32),
+          Err(e) => {
+            setup_error = Some(e);
+            break;
+          }
+        }
+      }
+      if let Some(e) = setup_error {
+        eprintln!(
+          "[BenchMatrix::Standalone] [ERROR] Suite '{}', Benchmark ID '{}': Setup failed: {:?}. Skipping this configuration.",
+          self.suite_base_name, full_id, e
+        );
+        report.skipped.push((full_id, format!("setup failed: {:?}", e)));
+        continue;
+      }
+
+      report.variants.push(StandaloneVariantReport {
+        id: full_id,
+        combination: variant.combination,
+        stats: SampleStats::from_samples(&samples).expect("at least one sample"),
+        iterations_per_sample: self.iterations_per_sample,
+        samples,
+      });
+    }
+
+    println!("{}", report.render());
+    report
+  }
+
+  /// Runs every combination once as a test instead of measuring it: setup, one iteration of
+  /// the logic and teardown. Each combination is reported in libtest's format; extractor and
+  /// setup errors and panics are failures. Call `assert_passed()` on the result from a
+  /// `#[test]` function.
+  pub fn run_tests(self) -> TestReport {
+    let mut report = TestReport::new(&self.suite_base_name);
+    let plan = match self.plan() {
+      Ok(plan) => plan,
+      Err(e) => {
+        report.error = Some(e);
+        println!("{}", report.render_summary());
+        return report;
+      }
+    };
+
+    println!("\nrunning {} tests", plan.variants.len());
+    for variant in plan.variants {
+      let outcome = match variant.config {
+        Ok(ref cfg) => run_case(|| {
+          self
+            .run_batch(cfg, 1)
+            .map(|_| ())
+            .map_err(|e| format!("setup failed: {:?}", e))
+        }),
+        Err(ref reason) => skip_outcome(reason),
+      };
+      report.record(variant.full_id(), outcome);
+    }
+    println!("\n{}", report.render_summary());
+    report
+  }
+
+  fn check_settings(&self) -> Result<(), String> {
+    if self.samples == 0 {
+      return Err("samples must be at least 1".to_string());
+    }
+    if self.iterations_per_sample == 0 || self.iterations_per_sample > u64::from(u32::MAX) {
+      return Err(format!("iterations_per_sample must be between 1 and {}", u32::MAX));
+    }
+    Ok(())
+  }
+
+  /// Sets up, runs `iterations` iterations and tears down, returning the summed durations
+  /// reported by the logic function.
+  fn run_batch(&self, cfg: &Cfg, iterations: u64) -> Result<Duration, SetupErr> {
+    let (mut ctx, mut state) = (self.setup_fn)(cfg)?;
+    let mut total = Duration::ZERO;
+    for _ in 0..iterations {
+      let elapsed;
+      (ctx, state, elapsed) = (self.benchmark_logic_fn)(ctx, state, cfg);
+      total += elapsed;
+    }
+    (self.teardown_fn)(ctx, state, cfg);
+    Ok(total)
+  }
+}
+
+/// Statistics of the time per iteration over a variant's samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleStats {
+  pub mean: Duration,
+  pub median: Duration,
+  /// The sample standard deviation; zero for a single sample.
+  pub stddev: Duration,
+  pub min: Duration,
+  pub max: Duration,
+}
+
+impl SampleStats {
+  /// Computes the statistics of `samples`, or `None` if there are none.
+  pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+    if samples.is_empty() {
+      return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let n = sorted.len();
+    let median = if n % 2 == 1 {
+      sorted[n / 2]
+    } else {
+      (sorted[n / 2 - 1] + sorted[n / 2]) / 2
+    };
+
+    let secs: Vec<f64> = sorted.iter().map(Duration::as_secs_f64).collect();
+    let mean = secs.iter().sum::<f64>() / n as f64;
+    let variance = if n > 1 {
+      secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+    } else {
+      0.0
+    };
+
+    Some(Self {
+      mean: Duration::from_secs_f64(mean),
+      median,
+      stddev: Duration::from_secs_f64(variance.sqrt()),
+      min: sorted[0],
+      max: sorted[n - 1],
+    })
+  }
+}
+
+/// The results of one variant run by [`StandaloneSuite`].
+#[derive(Debug, Clone)]
+pub struct StandaloneVariantReport {
+  /// The full benchmark ID, as the Criterion suites would name it.
+  pub id: String,
+  /// The combination this variant was generated from.
+  pub combination: AbstractCombination,
+  /// The time per iteration of each sample, in run order.
+  pub samples: Vec<Duration>,
+  pub iterations_per_sample: u64,
+  pub stats: SampleStats,
+}
+
+/// The results of a [`StandaloneSuite`] run.
+#[derive(Debug, Clone, Default)]
+pub struct StandaloneReport {
+  pub suite_name: String,
+  /// The suite's axis names, if set, in axis order.
+  pub parameter_names: Option<Vec<String>>,
+  /// One report per measured variant, in run order.
+  pub variants: Vec<StandaloneVariantReport>,
+  /// The full ID of every variant that was not measured, with the reason.
+  pub skipped: Vec<(String, String)>,
+}
+
+impl StandaloneReport {
+  /// Returns the report of the variant with the given full benchmark ID.
+  pub fn variant(&self, id: &str) -> Option<&StandaloneVariantReport> {
+    self.variants.iter().find(|variant| variant.id == id)
+  }
+
+  /// Renders the report as an aligned table with one row per measured variant, followed by
+  /// the skipped variants.
+  pub fn render(&self) -> String {
+    const HEADERS: [&str; 6] = ["mean", "median", "stddev", "min", "max", "samples"];
+    let id_width = self
+      .variants
+      .iter()
+      .map(|variant| variant.id.len())
+      .max()
+      .unwrap_or(0)
+      .max("Benchmark ID".len());
+
+    let mut out = String::new();
+    let _ = writeln!(out, "Suite '{}' standalone results (time per iteration):", self.suite_name);
+    let _ = write!(out, "  {:<id_width$}", "Benchmark ID");
+    for header in HEADERS {
+      let _ = write!(out, "  {:>10}", header);
+    }
+    out.push('\n');
+    for variant in &self.variants {
+      let stats = &variant.stats;
+      let _ = write!(out, "  {:<id_width$}", variant.id);
+      for value in [stats.mean, stats.median, stats.stddev, stats.min, stats.max] {
+        let _ = write!(out, "  {:>10}", format_duration(value));
+      }
+      let _ = writeln!(out, "  {:>10}", variant.samples.len());
+    }
+    for (id, reason) in &self.skipped {
+      let _ = writeln!(out, "  {:<id_width$}  skipped ({})", id, reason);
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sample_stats() {
+    let micros = |values: &[u64]| values.iter().map(|&v| Duration::from_micros(v)).collect::<Vec<_>>();
+    let stats = SampleStats::from_samples(&micros(&[4, 2, 8, 6])).unwrap();
+    assert_eq!((stats.min, stats.max), (Duration::from_micros(2), Duration::from_micros(8)));
+    assert_eq!(stats.median, Duration::from_micros(5));
+    assert_eq!(stats.mean.as_nanos(), 5_000);
+    // Sample variance of [2, 4, 6, 8] is 20/3.
+    assert!((stats.stddev.as_secs_f64() * 1e6 - (20.0f64 / 3.0).sqrt()).abs() < 1e-3);
+    assert_eq!(SampleStats::from_samples(&micros(&[3])).unwrap().stddev, Duration::ZERO);
+    assert_eq!(SampleStats::from_samples(&[]), None);
+  }
+
+  #[test]
+  fn test_run_measures_every_sample_and_reports_skips() {
+    fn setup(cost: &u64) -> Result<(u64, u64), String> {
+      if *cost == 0 { Err("free lunch".to_string()) } else { Ok((0, *cost)) }
+    }
+    fn logic(calls: u64, cost: u64, _cfg: &u64) -> (u64, u64, Duration) {
+      (calls + 1, cost, Duration::from_micros(cost))
+    }
+
+    let axis = [0, 3, 7, 9].map(MatrixCellValue::Unsigned).to_vec();
+    let report = StandaloneSuite::new(
+      "Cost".to_string(),
+      Some(vec!["Cost".to_string()]),
+      vec![axis],
+      |combo: &AbstractCombination| match combo.get_u64(0)? {
+        9 => Err("too expensive".to_string()),
+        cost => Ok(cost),
+      },
+      setup,
+      logic,
+      |calls, _cost, _cfg| assert_eq!(calls, 2),
+    )
+    .warm_up_iterations(2)
+    .samples(4)
+    .iterations_per_sample(2)
+    .run();
+
+    let ids: Vec<&str> = report.variants.iter().map(|variant| variant.id.as_str()).collect();
+    assert_eq!(ids, ["Cost/Cost-3", "Cost/Cost-7"]);
+    let variant = report.variant("Cost/Cost-7").unwrap();
+    assert_eq!(variant.samples, vec![Duration::from_micros(7); 4]);
+    assert_eq!(variant.stats.stddev, Duration::ZERO);
+    let skipped: Vec<&str> = report.skipped.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(skipped, ["Cost/Cost-0", "Cost/Cost-9"]);
+    assert!(report.render().contains("7us"), "{}", report.render());
+  }
+}
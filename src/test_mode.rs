@@ -0,0 +1,163 @@
+//! Test mode: every combination of a suite run once as a correctness check, separate from
+//! timing.
+//!
+//! The suites' `run_tests()` runs each combination's setup, one iteration of its logic and its
+//! teardown, and reports each combination as an individual test in libtest's format. Extractor,
+//! validator and setup errors count as failures, as do panics; excluded combinations are
+//! ignored. Calling `assert_passed()` on the report from a `#[test]` function fails the test if
+//! any combination failed.
+
+use crate::plan::SkipReason;
+
+use std::any::Any;
+use std::fmt::{self, Write as _};
+use std::panic::{self, AssertUnwindSafe};
+
+/// The outcome of one combination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+  Passed,
+  /// The combination errored or panicked, with the message.
+  Failed(String),
+  /// The combination was excluded by a constraint.
+  Ignored,
+}
+
+impl fmt::Display for TestOutcome {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TestOutcome::Passed => write!(f, "ok"),
+      TestOutcome::Failed(_) => write!(f, "FAILED"),
+      TestOutcome::Ignored => write!(f, "ignored"),
+    }
+  }
+}
+
+/// One combination run in test mode.
+#[derive(Debug, Clone)]
+pub struct CombinationTest {
+  /// The full benchmark ID of the combination.
+  pub id: String,
+  pub outcome: TestOutcome,
+}
+
+/// The outcomes of a suite's `run_tests()`.
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+  pub suite_name: String,
+  /// One entry per combination, in plan order.
+  pub tests: Vec<CombinationTest>,
+  /// Set when the suite could not be planned, in which case no combination ran.
+  pub error: Option<String>,
+}
+
+impl TestReport {
+  pub(crate) fn new(suite_name: &str) -> Self {
+    Self {
+      suite_name: suite_name.to_string(),
+      ..Self::default()
+    }
+  }
+
+  /// Records the outcome of a combination and prints its libtest-style line.
+  pub(crate) fn record(&mut self, id: String, outcome: TestOutcome) {
+    println!("test {} ... {}", id, outcome);
+    self.tests.push(CombinationTest { id, outcome });
+  }
+
+  /// Returns the failed combinations.
+  pub fn failures(&self) -> impl Iterator<Item = &CombinationTest> {
+    self.tests.iter().filter(|test| matches!(test.outcome, TestOutcome::Failed(_)))
+  }
+
+  /// Returns whether the suite was planned and no combination failed.
+  pub fn passed(&self) -> bool {
+    self.error.is_none() && self.failures().next().is_none()
+  }
+
+  /// Renders the failure messages and the summary line, as libtest does after the test lines.
+  pub fn render_summary(&self) -> String {
+    let mut out = String::new();
+    if let Some(error) = &self.error {
+      let _ = writeln!(out, "Suite '{}' could not be planned: {}", self.suite_name, error);
+    }
+    let failures: Vec<&CombinationTest> = self.failures().collect();
+    if !failures.is_empty() {
+      let _ = writeln!(out, "failures:");
+      for test in &failures {
+        if let TestOutcome::Failed(message) = &test.outcome {
+          let _ = writeln!(out, "    {}: {}", test.id, message);
+        }
+      }
+    }
+    let count = |outcome: fn(&TestOutcome) -> bool| self.tests.iter().filter(|test| outcome(&test.outcome)).count();
+    let _ = writeln!(
+      out,
+      "test result: {}. {} passed; {} failed; {} ignored",
+      if self.passed() { "ok" } else { "FAILED" },
+      count(|outcome| *outcome == TestOutcome::Passed),
+      failures.len(),
+      count(|outcome| *outcome == TestOutcome::Ignored)
+    );
+    out
+  }
+
+  /// Panics with the failure messages if the suite could not be planned or any combination
+  /// failed.
+  pub fn assert_passed(&self) {
+    if !self.passed() {
+      panic!("Suite '{}' failed in test mode:\n{}", self.suite_name, self.render_summary());
+    }
+  }
+}
+
+/// Maps the reason a planned combination will not run to its test outcome.
+pub(crate) fn skip_outcome(reason: &SkipReason) -> TestOutcome {
+  match reason {
+    SkipReason::Excluded => TestOutcome::Ignored,
+    other => TestOutcome::Failed(other.to_string()),
+  }
+}
+
+/// Runs one combination, turning an error or a panic into a failure.
+pub(crate) fn run_case(case: impl FnOnce() -> Result<(), String>) -> TestOutcome {
+  match catch_panic(case) {
+    Ok(()) => TestOutcome::Passed,
+    Err(e) => TestOutcome::Failed(e),
+  }
+}
+
+/// Runs `f`, turning a panic into an error.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+  panic::catch_unwind(AssertUnwindSafe(f))
+    .unwrap_or_else(|payload| Err(format!("panicked: {}", panic_message(payload.as_ref()))))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+  payload
+    .downcast_ref::<&str>()
+    .copied()
+    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+    .unwrap_or("<non-string panic payload>")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_errors_and_panics_fail_and_exclusions_are_ignored() {
+    let mut report = TestReport::new("Suite");
+    report.record("Suite/A-1".to_string(), run_case(|| Ok(())));
+    report.record("Suite/A-2".to_string(), run_case(|| Err("setup failed: refused".to_string())));
+    report.record("Suite/A-3".to_string(), run_case(|| panic!("index {} out of range", 7)));
+    report.record("Suite/A-4".to_string(), skip_outcome(&SkipReason::Excluded));
+
+    assert!(!report.passed());
+    assert_eq!(report.tests[2].outcome, TestOutcome::Failed("panicked: index 7 out of range".to_string()));
+    let summary = report.render_summary();
+    assert!(summary.contains("    Suite/A-2: setup failed: refused"), "{}", summary);
+    assert!(summary.ends_with("test result: FAILED. 1 passed; 2 failed; 1 ignored\n"), "{}", summary);
+    assert!(panic::catch_unwind(|| report.assert_passed()).is_err());
+  }
+}
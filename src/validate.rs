@@ -0,0 +1,183 @@
+//! Up-front validation of a whole suite matrix, so that every configuration problem is
+//! reported at once before any benchmark time is spent.
+
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+use crate::plan::{MatrixPlan, SkipReason};
+
+use std::fmt;
+
+/// A single problem found while validating a suite's matrix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationProblem {
+  /// The number of parameter names does not match the number of axes.
+  AxisCountMismatch { names: usize, axes: usize },
+  /// The filters could not be applied to the axes.
+  Filter(String),
+  /// A non-fatal planning problem, such as a constraint naming an unknown axis.
+  Plan(String),
+  /// The matrix produces no combinations at all.
+  NoCombinations,
+  /// The extractor failed for a combination (identified by its full benchmark ID).
+  Extraction { bench_id: String, error: String },
+  /// The configuration validator rejected an extracted configuration.
+  InvalidConfig { bench_id: String, error: String },
+  /// Several runnable combinations map to the same benchmark ID
+  /// (see [`IdFormat::Reversible`](crate::IdFormat::Reversible)).
+  DuplicateId { bench_id: String, combinations: Vec<String> },
+}
+
+impl fmt::Display for ValidationProblem {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ValidationProblem::AxisCountMismatch { names, axes } => write!(
+        f,
+        "{} parameter names were given for {} parameter axes",
+        names, axes
+      ),
+      ValidationProblem::Filter(e) => write!(f, "filter could not be applied: {}", e),
+      ValidationProblem::Plan(e) => write!(f, "{}", e),
+      ValidationProblem::NoCombinations => write!(f, "the matrix produces no combinations"),
+      ValidationProblem::Extraction { bench_id, error } => {
+        write!(f, "'{}': extraction failed: {}", bench_id, error)
+      }
+      ValidationProblem::InvalidConfig { bench_id, error } => {
+        write!(f, "'{}': invalid configuration: {}", bench_id, error)
+      }
+      ValidationProblem::DuplicateId { bench_id, combinations } => write!(
+        f,
+        "benchmark ID '{}' is shared by {} combinations: {}",
+        bench_id,
+        combinations.len(),
+        combinations.join(", ")
+      ),
+    }
+  }
+}
+
+/// Every problem found while validating a suite, returned by the suites' `validate()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+  /// The suite (Criterion group) name.
+  pub suite_name: String,
+  /// The problems found, in the order they were detected.
+  pub problems: Vec<ValidationProblem>,
+}
+
+impl fmt::Display for ValidationError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "Suite '{}' failed validation with {} problem(s):",
+      self.suite_name,
+      self.problems.len()
+    )?;
+    for problem in &self.problems {
+      write!(f, "\n  - {}", problem)?;
+    }
+    Ok(())
+  }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Collects the problems of a suite given its declared names/axes and the outcome of planning.
+#[cfg(any(feature = "criterion_integration", feature = "standalone"))]
+pub(crate) fn validate_plan<Cfg>(
+  suite_name: &str,
+  parameter_names: Option<&[String]>,
+  axis_count: usize,
+  plan: Result<MatrixPlan<Cfg>, String>,
+) -> Result<(), ValidationError> {
+  let mut problems = Vec::new();
+
+  if let Some(names) = parameter_names
+    && names.len() != axis_count
+  {
+    problems.push(ValidationProblem::AxisCountMismatch {
+      names: names.len(),
+      axes: axis_count,
+    });
+  }
+
+  match plan {
+    Err(e) => problems.push(ValidationProblem::Filter(e)),
+    Ok(plan) => {
+      problems.extend(plan.warnings.iter().cloned().map(ValidationProblem::Plan));
+      if plan.variants.is_empty() {
+        problems.push(ValidationProblem::NoCombinations);
+      }
+      for variant in &plan.variants {
+        match &variant.config {
+          Err(SkipReason::Extraction(error)) => problems.push(ValidationProblem::Extraction {
+            bench_id: variant.full_id(),
+            error: error.clone(),
+          }),
+          Err(SkipReason::InvalidConfig(error)) => problems.push(ValidationProblem::InvalidConfig {
+            bench_id: variant.full_id(),
+            error: error.clone(),
+          }),
+          Ok(_) | Err(SkipReason::Excluded) | Err(SkipReason::DuplicateId(_)) => {}
+        }
+      }
+      for (bench_id, indices) in plan.duplicate_ids() {
+        problems.push(ValidationProblem::DuplicateId {
+          bench_id,
+          combinations: indices
+            .iter()
+            .map(|&index| format!("{:?}", plan.variants[index].combination.cells))
+            .collect(),
+        });
+      }
+    }
+  }
+
+  if problems.is_empty() {
+    Ok(())
+  } else {
+    Err(ValidationError {
+      suite_name: suite_name.to_string(),
+      problems,
+    })
+  }
+}
+
+#[cfg(all(test, any(feature = "criterion_integration", feature = "standalone")))]
+mod tests {
+  use super::*;
+  use crate::params::{AbstractCombination, MatrixCellValue};
+  use crate::plan::PlannedVariant;
+
+  fn variant(id: &str, cells: Vec<MatrixCellValue>, config: Result<u32, SkipReason>) -> PlannedVariant<u32> {
+    PlannedVariant {
+      combination: AbstractCombination { cells },
+      group_name: "Suite".to_string(),
+      bench_id: id.to_string(),
+      parameter: None,
+      config,
+    }
+  }
+
+  #[test]
+  fn test_validate_plan_collects_all_problems() {
+    let names = vec!["Name".to_string(), "Extra".to_string()];
+    let plan = MatrixPlan {
+      suite_name: "Suite".to_string(),
+      parameter_names: Some(names.clone()),
+      parameter_axes: vec![],
+      variants: vec![
+        variant("a_b", vec![MatrixCellValue::String("a b".to_string())], Ok(1)),
+        variant("a_b", vec![MatrixCellValue::String("a_b".to_string())], Ok(2)),
+        variant("c", vec![MatrixCellValue::Tag("c".to_string())], Err(SkipReason::Extraction("bad".to_string()))),
+        variant("d", vec![MatrixCellValue::Tag("d".to_string())], Err(SkipReason::Excluded)),
+      ],
+      warnings: vec![],
+    };
+
+    let err = validate_plan("Suite", Some(&names), 1, Ok(plan)).unwrap_err();
+    assert_eq!(err.problems.len(), 3, "unexpected problems: {}", err);
+    assert_eq!(err.problems[0], ValidationProblem::AxisCountMismatch { names: 2, axes: 1 });
+    assert!(matches!(&err.problems[1], ValidationProblem::Extraction { bench_id, .. } if bench_id == "Suite/c"));
+    assert!(matches!(&err.problems[2], ValidationProblem::DuplicateId { bench_id, combinations } if bench_id == "Suite/a_b" && combinations.len() == 2));
+    assert!(err.to_string().starts_with("Suite 'Suite' failed validation with 3 problem(s):"));
+  }
+}